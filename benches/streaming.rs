@@ -0,0 +1,57 @@
+//! Compares decoding many small chunks with a `StreamingDecoder`, which
+//! reuses its buffer, against a `Decoder`, which allocates each body.
+//! Run with `cargo bench`.
+use std::time::{Duration, Instant};
+use iffc::{StreamingDecoder, Decoder, Encoder, Chunk, FourCC};
+
+const CHUNKS: usize = 100_000;
+const ROUNDS: u32 = 10;
+
+fn stream() -> Vec<u8> {
+    let mut enc = Encoder::new(Vec::new());
+
+    for n in 0..CHUNKS {
+        let body = vec![n as u8; n % 64];
+        enc.write_chunk(&Chunk(FourCC(*b"pkt "), body.into())).unwrap();
+    }
+
+    enc.into_inner()
+}
+
+/// Shortest of `ROUNDS` runs of `f`, which returns the number of body
+/// bytes it saw so that the work can't be optimized away.
+fn time<F: FnMut() -> usize>(mut f: F) -> Duration {
+    let mut best = Duration::MAX;
+    let expected = f();
+
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        assert_eq!(f(), expected);
+        best = best.min(start.elapsed());
+    }
+
+    best
+}
+
+fn main() {
+    let inp = stream();
+
+    let streaming = time(|| {
+        let mut dec = StreamingDecoder::new(&inp[..]);
+        let mut len = 0;
+
+        while let Some(chunk) = dec.next() {
+            len += chunk.unwrap().1.len();
+        }
+
+        len
+    });
+
+    let allocating = time(|| {
+        Decoder::new(&inp[..]).map(|c| c.unwrap().1.len()).sum()
+    });
+
+    for (name, t) in [("StreamingDecoder", streaming), ("Decoder", allocating)] {
+        println!("{:<16} {:>8.1} ns/chunk", name, t.as_nanos() as f64 / CHUNKS as f64);
+    }
+}
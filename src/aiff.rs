@@ -0,0 +1,140 @@
+//! Typed views of the chunks of an Apple `AIFF` file.
+use crate::FormatError;
+
+/// The body of a `COMM` chunk, describing the sound data of the file.
+/// All of its fields are big-endian.
+///
+/// ```
+/// use iffc::aiff::Comm;
+/// use iffc::FormatError;
+///
+/// // 16-bit stereo, 1000 frames at 44.1 kHz.
+/// let body = b"\x00\x02\x00\x00\x03\xE8\x00\x10\x40\x0E\xAC\x44\x00\x00\x00\x00\x00\x00";
+/// let comm = Comm::parse(body).unwrap();
+///
+/// assert_eq!((comm.channels, comm.sample_frames, comm.bits_per_sample), (2, 1000, 16));
+/// assert!((comm.sample_rate - 44100.0).abs() < 1e-9);
+///
+/// assert_eq!(Comm::parse(&body[..10]), Err(FormatError::TooShort { expected: 18, got: 10 }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comm {
+    pub channels: u16,
+    pub sample_frames: u32,
+    pub bits_per_sample: u16,
+    /// Samples per second, stored in the chunk as an 80-bit float.
+    pub sample_rate: f64
+}
+
+impl Comm {
+    /// Parses the body of a `COMM` chunk, which must be 18 bytes long
+    /// at least. The fields AIFF-C appends after those are ignored.
+    pub fn parse(body: &[u8]) -> Result<Self, FormatError> {
+        if body.len() < 18
+        { return Err(FormatError::TooShort { expected: 18, got: body.len() }) }
+
+        let mut rate = [0u8; 10];
+        rate.copy_from_slice(&body[8..18]);
+
+        Ok(Self {
+            channels: u16::from_be_bytes([body[0], body[1]]),
+            sample_frames: u32::from_be_bytes([body[2], body[3], body[4], body[5]]),
+            bits_per_sample: u16::from_be_bytes([body[6], body[7]]),
+            sample_rate: extended_to_f64(rate)
+        })
+    }
+}
+
+/// The body of an `SSND` chunk, whose sample data only starts after two
+/// big-endian fields and as many bytes as the first of them tells, which
+/// writers use to align the samples to blocks of `block_size` bytes.
+///
+/// ```
+/// use iffc::aiff::Ssnd;
+/// use iffc::FormatError;
+///
+/// // samples aligned to 4 bytes after 2 bytes of padding.
+/// let body = b"\x00\x00\x00\x02\x00\x00\x00\x04\xFF\xFF\x01\x02\x03\x04";
+/// let ssnd = Ssnd::parse(body).unwrap();
+///
+/// assert_eq!((ssnd.offset, ssnd.block_size), (2, 4));
+/// assert_eq!(ssnd.samples, b"\x01\x02\x03\x04");
+///
+/// assert_eq!(Ssnd::parse(&body[..9]), Err(FormatError::TooShort { expected: 10, got: 9 }));
+/// assert_eq!(Ssnd::parse(&body[..6]), Err(FormatError::TooShort { expected: 8, got: 6 }));
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Ssnd<'a> {
+    /// Bytes between the end of the fields and the first sample.
+    pub offset: u32,
+    /// Size of the blocks the samples are aligned to, usually 0.
+    pub block_size: u32,
+    pub samples: &'a [u8]
+}
+
+impl<'a> Ssnd<'a> {
+    /// Parses the body of an `SSND` chunk, which must hold the 8 bytes of
+    /// the fields and the `offset` bytes after them at least.
+    pub fn parse(body: &'a [u8]) -> Result<Self, FormatError> {
+        if body.len() < 8
+        { return Err(FormatError::TooShort { expected: 8, got: body.len() }) }
+
+        let offset = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+        let block_size = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+
+        let start = (offset as usize).saturating_add(8);
+        let samples = body.get(start..)
+            .ok_or(FormatError::TooShort { expected: start, got: body.len() })?;
+
+        Ok(Self { offset, block_size, samples })
+    }
+}
+
+/// Converts a big-endian 80-bit IEEE 754 extended float, which has a
+/// sign bit, a 15-bit exponent biased by 16383 and a 64-bit mantissa
+/// whose integer bit is explicit, into the nearest `f64`.
+/// ```
+/// use iffc::aiff::extended_to_f64;
+///
+/// let rate = |b: &[u8]| { let mut r = [0u8; 10]; r[..b.len()].copy_from_slice(b); extended_to_f64(r) };
+///
+/// assert_eq!(rate(b"\x40\x0E\xBB\x80"), 48000.0);
+/// assert_eq!(rate(b"\x40\x0B\xFA"), 8000.0);
+/// assert_eq!(rate(b"\xBF\xFF\x80"), -1.0);
+/// assert_eq!(rate(b"\x3F\xFE\xC0"), 0.75);
+/// assert_eq!(rate(b""), 0.0);
+/// assert_eq!(rate(b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01"), 0.0);
+/// assert!(rate(b"\x7F\xFF").is_infinite());
+/// assert!(rate(b"\x7F\xFF\xC0").is_nan());
+/// ```
+pub fn extended_to_f64(b: [u8; 10]) -> f64 {
+    let sign = if b[0] & 0x80 != 0 { -1.0 } else { 1.0 };
+    let exponent = (u16::from_be_bytes([b[0], b[1]]) & 0x7FFF) as i32;
+
+    let mut mantissa = [0u8; 8];
+    mantissa.copy_from_slice(&b[2..]);
+    let mantissa = u64::from_be_bytes(mantissa);
+
+    if exponent == 0x7FFF {
+        // the integer bit doesn't matter, only the fraction tells apart
+        // infinities from NaNs.
+        return if mantissa << 1 == 0 { sign * f64::INFINITY } else { f64::NAN }
+    }
+
+    // denormals have an exponent of 1 despite being stored as 0.
+    let e = exponent.max(1) - 16383 - 63;
+
+    // scaled in two steps, as 2^e alone may not be representable.
+    sign * mantissa as f64 * pow2(e / 2) * pow2(e - e / 2)
+}
+
+/// `2^e`, built from its bits since `powi` needs `std`. Too large an
+/// `e` gives an infinity and too small a one zero, as `powi` would.
+fn pow2(e: i32) -> f64 {
+    match e {
+        1024..=i32::MAX => f64::INFINITY,
+        -1022..=1023 => f64::from_bits(((e + 1023) as u64) << 52),
+        -1074..=-1023 => f64::from_bits(1 << (e + 1074)),
+        _ => 0.0
+    }
+}
@@ -0,0 +1,226 @@
+//! Helpers for AVI files, a `RIFF` of form type `AVI ` keeping its
+//! media in a `LIST` of form type `movi`. Each chunk of it holds data
+//! of one stream, told by its id: two decimal digits numbering the
+//! stream then two letters for the kind of data, such as `00dc` for
+//! compressed video or `01wb` for audio. The `idx1` chunk following
+//! the list locates each of these chunks.
+use std::collections::VecDeque;
+use std::io::{Read, Seek};
+use crate::{Chunk, FourCC, DecodeError, FormatError, GroupingDecoder, GroupHandle, Children, Entry};
+
+/// Flag of an `IndexEntry` whose chunk is a key frame.
+pub const AVIIF_KEYFRAME: u32 = 0x10;
+
+/// An entry of the `idx1` chunk, locating the chunk of `size` bytes
+/// with the identity `id` at `offset`, whose meaning depends on the
+/// `IndexBase` of the file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct IndexEntry {
+    pub id: FourCC,
+    pub flags: u32,
+    pub offset: u32,
+    pub size: u32
+}
+
+/// What the offsets of an `idx1` chunk are counted from. Most files
+/// count them from the form type of the `movi` list, but some writers
+/// store them from the start of the file instead.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum IndexBase {
+    Movi,
+    File
+}
+
+/// A chunk of a `movi` list along with the stream it belongs to.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Frame {
+    pub stream: u16,
+    pub kind: [u8; 2],
+    pub chunk: Chunk
+}
+
+impl IndexEntry {
+    /// Whether the chunk is a key frame.
+    pub fn is_keyframe(&self) -> bool { self.flags & AVIIF_KEYFRAME != 0 }
+
+    /// Offset of the header of the chunk from the start of the file,
+    /// `movi` being the list the chunk is in.
+    pub fn resolve(&self, movi: &GroupHandle, base: IndexBase) -> u64 {
+        match base {
+            IndexBase::Movi => movi.offset.saturating_sub(4).saturating_add(self.offset as u64),
+            IndexBase::File => self.offset as u64
+        }
+    }
+}
+
+impl IndexBase {
+    /// Guesses the base of `entries` from the first of them: offsets
+    /// counted from the `movi` list start small, those counted from the
+    /// start of the file can't come before the list. Without entries
+    /// `Movi` is assumed, as the more common one.
+    pub fn detect(entries: &[IndexEntry], movi: &GroupHandle) -> Self {
+        match entries.first() {
+            Some(e) if e.offset as u64 >= movi.offset.saturating_sub(4) => Self::File,
+            _ => Self::Movi
+        }
+    }
+}
+
+/// Parses the body of an `idx1` chunk, made of 16-byte entries. A body
+/// whose length isn't a multiple of 16 is reported as too short.
+pub fn parse_idx1(body: &[u8]) -> Result<Vec<IndexEntry>, FormatError> {
+    if body.len() % 16 != 0
+    { return Err(FormatError::TooShort { expected: body.len() / 16 * 16 + 16, got: body.len() }) }
+
+    let u32_at = |e: &[u8], i: usize| u32::from_le_bytes([e[i], e[i + 1], e[i + 2], e[i + 3]]);
+
+    Ok(body.chunks_exact(16).map(|e| IndexEntry {
+        id: FourCC([e[0], e[1], e[2], e[3]]),
+        flags: u32_at(e, 4),
+        offset: u32_at(e, 8),
+        size: u32_at(e, 12)
+    }).collect())
+}
+
+/// Splits the id of a chunk of a `movi` list into the number of its
+/// stream and its kind, `None` if it doesn't start with two digits.
+/// ```
+/// use iffc::FourCC;
+/// use iffc::avi::stream_of;
+///
+/// assert_eq!(stream_of(FourCC(*b"01wb")), Some((1, *b"wb")));
+/// assert_eq!(stream_of(FourCC(*b"ix00")), None);
+/// ```
+pub fn stream_of(id: FourCC) -> Option<(u16, [u8; 2])> {
+    let [a, b, c, d] = id.0;
+
+    if !a.is_ascii_digit() || !b.is_ascii_digit() { return None }
+    Some(((a - b'0') as u16 * 10 + (b - b'0') as u16, [c, d]))
+}
+
+/// Looks for the `movi` list among the children of `riff`, which must
+/// have been yielded by `dec`.
+pub fn find_movi<R: Read + Seek>(dec: &mut GroupingDecoder<R>, riff: &GroupHandle)
+    -> Result<Option<GroupHandle>, DecodeError>
+{
+    for entry in dec.children(riff) {
+        match entry? {
+            Entry::Group(g) if g.id == "LIST" && g.form_type == "movi" => return Ok(Some(g)),
+            _ => {}
+        }
+    }
+
+    Ok(None)
+}
+
+/// Iterates over the frames of `movi`, the list found by `find_movi`.
+/// See `Frames`.
+/// ```
+/// use std::io::Cursor;
+/// use iffc::{GroupingDecoder, Decoder, Encoder, Entry, Chunk, FourCC};
+/// use iffc::avi::{self, IndexBase};
+///
+/// let frame = |id: &[u8; 4], body: &[u8]| Chunk(FourCC(*id), body.into());
+///
+/// let mut enc = Encoder::new(Cursor::new(Vec::new()));
+/// let mut riff = enc.begin_group(FourCC(*b"RIFF"), FourCC(*b"AVI "))?;
+///
+/// let mut hdrl = riff.begin_group(FourCC(*b"LIST"), FourCC(*b"hdrl"))?;
+/// hdrl.write_chunk(&frame(b"avih", &[0; 4]))?;
+/// hdrl.finish()?;
+///
+/// let mut movi = riff.begin_group(FourCC(*b"LIST"), FourCC(*b"movi"))?;
+/// movi.write_chunk(&frame(b"00dc", b"key"))?;
+/// movi.write_chunk(&frame(b"01wb", b"au"))?;
+/// movi.write_chunk(&frame(b"JUNK", &[0; 2]))?;
+/// movi.write_chunk(&frame(b"00dc", b"next"))?;
+/// movi.finish()?;
+///
+/// // offsets from the `movi` form type: 4, 4 + 12, 4 + 12 + 10 + 10.
+/// let mut idx1 = Vec::new();
+/// for &(id, flags, offset, size) in &[(b"00dc", 0x10u32, 4u32, 3u32), (b"01wb", 0, 16, 2), (b"00dc", 0, 36, 4)] {
+///     idx1.extend(&id[..]);
+///     idx1.extend(flags.to_le_bytes().iter().chain(&offset.to_le_bytes()).chain(&size.to_le_bytes()));
+/// }
+/// riff.write_chunk(&Chunk(FourCC(*b"idx1"), idx1.into()))?;
+/// riff.finish()?;
+///
+/// let out = enc.into_inner().into_inner();
+/// let mut dec = GroupingDecoder::new(Cursor::new(&out[..]));
+///
+/// let riff = match dec.next().unwrap()? { Entry::Group(g) => g, e => panic!("{:?}", e) };
+/// let movi = avi::find_movi(&mut dec, &riff)?.unwrap();
+///
+/// let frames = avi::frames(&mut dec, &movi).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(frames.iter().map(|f| (f.stream, f.kind)).collect::<Vec<_>>(),
+///     [(0, *b"dc"), (1, *b"wb"), (0, *b"dc")]);
+/// assert_eq!(frames[2].chunk, frame(b"00dc", b"next"));
+///
+/// let idx1 = dec.children(&riff)
+///     .filter_map(|e| match e { Ok(Entry::Leaf(c)) if c.0 == "idx1" => Some(c), _ => None })
+///     .next().unwrap();
+/// let entries = avi::parse_idx1(&idx1.1).unwrap();
+/// assert!(entries[0].is_keyframe());
+///
+/// let base = IndexBase::detect(&entries, &movi);
+/// assert_eq!(base, IndexBase::Movi);
+///
+/// let at = entries[2].resolve(&movi, base) as usize;
+/// assert_eq!(Decoder::new(&out[at..]).next().unwrap()?, frame(b"00dc", b"next"));
+///
+/// // the same entries, counted from the start of the file.
+/// let absolute = entries.iter()
+///     .map(|e| avi::IndexEntry { offset: e.resolve(&movi, base) as u32, ..*e })
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(IndexBase::detect(&absolute, &movi), IndexBase::File);
+/// assert_eq!(absolute[2].resolve(&movi, IndexBase::File), at as u64);
+/// # Ok::<(), iffc::DecodeError>(())
+/// ```
+pub fn frames<'a, R: Read + Seek>(dec: &'a mut GroupingDecoder<R>, movi: &GroupHandle) -> Frames<'a, R>
+{ Frames { children: dec.children(movi), rec: VecDeque::new() } }
+
+/// Iterator over the frames of a `movi` list, see `frames`. Its chunks
+/// whose id doesn't start with a stream number, such as `JUNK` or the
+/// `ix00` indices of OpenDML files, are skipped. The `LIST`s of form
+/// type `rec `, which group the chunks of the streams played together,
+/// are read whole and their frames yielded in turn.
+pub struct Frames<'a, R: Read + Seek> {
+    children: Children<'a, R>,
+    rec: VecDeque<Frame>
+}
+
+impl<R: Read + Seek> Frames<'_, R> {
+    /// Wraps `chunk` into a frame if it belongs to a stream.
+    fn frame(chunk: Chunk) -> Option<Frame> {
+        let (stream, kind) = stream_of(chunk.0)?;
+        Some(Frame { stream, kind, chunk })
+    }
+}
+
+impl<R: Read + Seek> Iterator for Frames<'_, R> {
+    type Item = Result<Frame, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(frame) = self.rec.pop_front()
+            { return Some(Ok(frame)) }
+
+            match self.children.next()? {
+                Ok(Entry::Leaf(chunk)) => if let Some(frame) = Self::frame(chunk)
+                    { return Some(Ok(frame)) },
+                Ok(Entry::Group(g)) if g.form_type == "rec " => {
+                    for entry in self.children.children(&g) {
+                        match entry {
+                            Ok(Entry::Leaf(chunk)) => self.rec.extend(Self::frame(chunk)),
+                            Ok(Entry::Group(_)) => {},
+                            Err(e) => return Some(Err(e))
+                        }
+                    }
+                },
+                Ok(Entry::Group(_)) => {},
+                Err(e) => return Some(Err(e))
+            }
+        }
+    }
+}
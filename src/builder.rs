@@ -0,0 +1,207 @@
+use std::io::{Read, Write};
+use crate::{Decoder, Encoder, FourCC, Endianness, Alignment, FieldOrder, Layout, DEFAULT_MAX_CHUNK_SIZE};
+use crate::decoder::FILLER_IDS;
+
+/// The options of a `Decoder` gathered up front, so that they can be
+/// set in any order and reused for several readers. Each setter does
+/// what the `Decoder` method of the same name prefixed with `with_`
+/// does, their defaults being those of `Decoder::new`.
+/// ```
+/// use iffc::{DecoderBuilder, Decoder, Chunk, FourCC, Endianness, Alignment, DecodeError};
+///
+/// let builder = DecoderBuilder::new()
+///     .endianness(Endianness::Big)
+///     .alignment(Alignment::None)
+///     .max_chunk_size(4)
+///     .strict(true)
+///     .skip_filler(true);
+///
+/// // big-endian sizes, no padding and the `JUNK` skipped.
+/// let inp = b"JUNK\x00\x00\x00\x01xabc \x00\x00\x00\x03xyz";
+/// assert_eq!(builder.build(&inp[..]).collect::<Result<Vec<Chunk>, _>>()?,
+///     [Chunk(FourCC(*b"abc "), Box::new(*b"xyz"))]);
+///
+/// let large = b"abc \x00\x00\x00\x05vwxyz";
+/// assert!(matches!(builder.build(&large[..]).next(), Some(Err(DecodeError::ChunkTooLarge { .. }))));
+///
+/// let invalid = b"ab\0 \x00\x00\x00\x00";
+/// assert!(matches!(builder.build(&invalid[..]).next(), Some(Err(DecodeError::InvalidId { .. }))));
+///
+/// // the same default options as `Decoder::new`.
+/// let inp = b"JUNK\x01\x00\x00\x00x\0";
+/// assert_eq!(DecoderBuilder::new().build(&inp[..]).count(), Decoder::new(&inp[..]).count());
+/// # Ok::<(), DecodeError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct DecoderBuilder {
+    layout: Layout,
+    max_chunk_size: u32,
+    max_chunks: u32,
+    budget: u64,
+    strict: bool,
+    resync: bool,
+    verify: bool,
+    filler: Option<Vec<FourCC>>,
+    padded_sizes: bool,
+    unsized_containers: bool
+}
+
+impl DecoderBuilder {
+    /// Options of `Decoder::new`, reading little-endian (RIFF) chunks.
+    pub fn new() -> Self {
+        Self {
+            layout: Layout::RIFF,
+            max_chunk_size: DEFAULT_MAX_CHUNK_SIZE,
+            max_chunks: u32::MAX,
+            budget: u64::MAX,
+            strict: false,
+            resync: false,
+            verify: true,
+            filler: None,
+            padded_sizes: false,
+            unsized_containers: false
+        }
+    }
+
+    pub fn endianness(mut self, endianness: Endianness) -> Self
+    { self.layout.endianness = endianness; self }
+
+    pub fn alignment(mut self, alignment: Alignment) -> Self
+    { self.layout.alignment = alignment; self }
+
+    pub fn field_order(mut self, order: FieldOrder) -> Self
+    { self.layout.field_order = order; self }
+
+    /// Replaces the endianness, alignment and field order set so far.
+    pub fn layout(mut self, layout: Layout) -> Self
+    { self.layout = layout; self }
+
+    pub fn max_chunk_size(mut self, max: u32) -> Self
+    { self.max_chunk_size = max; self }
+
+    pub fn max_chunks(mut self, max: u32) -> Self
+    { self.max_chunks = max; self }
+
+    pub fn memory_budget(mut self, budget: u64) -> Self
+    { self.budget = budget; self }
+
+    pub fn strict(mut self, strict: bool) -> Self
+    { self.strict = strict; self }
+
+    pub fn resync(mut self, resync: bool) -> Self
+    { self.resync = resync; self }
+
+    pub fn verify_checksums(mut self, verify: bool) -> Self
+    { self.verify = verify; self }
+
+    pub fn skip_filler(mut self, skip: bool) -> Self {
+        self.filler = match (skip, self.filler) {
+            (false, _) => None,
+            (true, Some(ids)) => Some(ids),
+            (true, None) => Some(FILLER_IDS.to_vec())
+        };
+        self
+    }
+
+    pub fn filler_ids<I: IntoIterator<Item = FourCC>>(mut self, ids: I) -> Self
+    { self.filler = Some(ids.into_iter().collect()); self }
+
+    pub fn padded_sizes(mut self, padded: bool) -> Self
+    { self.padded_sizes = padded; self }
+
+    pub fn unsized_containers(mut self, tolerate: bool) -> Self
+    { self.unsized_containers = tolerate; self }
+
+    /// A decoder of the chunks of `r` with these options.
+    pub fn build<R: Read>(&self, r: R) -> Decoder<R> {
+        let dec = Decoder::new(r)
+            .with_layout(self.layout)
+            .with_max_chunk_size(self.max_chunk_size)
+            .with_max_chunks(self.max_chunks)
+            .with_memory_budget(self.budget)
+            .with_strict(self.strict)
+            .with_resync(self.resync)
+            .with_verify_checksums(self.verify)
+            .with_padded_sizes(self.padded_sizes)
+            .with_unsized_containers(self.unsized_containers);
+
+        match &self.filler {
+            Some(ids) => dec.with_filler_ids(ids.iter().copied()),
+            None => dec
+        }
+    }
+}
+
+impl Default for DecoderBuilder
+{ fn default() -> Self { Self::new() } }
+
+/// The options of an `Encoder`, the counterpart of `DecoderBuilder`.
+/// An encoder and a decoder built with the same endianness, alignment
+/// or layout agree on how chunks are framed.
+/// ```
+/// use iffc::{EncoderBuilder, DecoderBuilder, Chunk, FourCC, Endianness, Alignment};
+///
+/// let chunks = (0..9u8)
+///     .map(|n| Chunk(FourCC([b'a' + n, b'b', b'c', b'd']), vec![n; n as usize].into()))
+///     .collect::<Vec<_>>();
+///
+/// let mut enc = EncoderBuilder::new()
+///     .endianness(Endianness::Big)
+///     .alignment(Alignment::Four)
+///     .pad_byte(0xFF)
+///     .build(Vec::new());
+/// enc.write_all(chunks.iter().cloned())?;
+///
+/// let out = enc.into_inner();
+/// assert_eq!(&out[..16], b"abcd\x00\x00\x00\x00bbcd\x00\x00\x00\x01");
+/// assert_eq!(&out[16..20], b"\x01\xFF\xFF\xFF");
+///
+/// let dec = DecoderBuilder::new()
+///     .endianness(Endianness::Big)
+///     .alignment(Alignment::Four)
+///     .build(&out[..]);
+/// assert_eq!(dec.collect::<Result<Vec<Chunk>, _>>().unwrap(), chunks);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncoderBuilder {
+    layout: Layout,
+    pad: u8,
+    unique: Vec<FourCC>
+}
+
+impl EncoderBuilder {
+    /// Options of `Encoder::new`, writing little-endian (RIFF) chunks.
+    pub fn new() -> Self
+    { Self { layout: Layout::RIFF, pad: 0, unique: Vec::new() } }
+
+    pub fn endianness(mut self, endianness: Endianness) -> Self
+    { self.layout.endianness = endianness; self }
+
+    pub fn alignment(mut self, alignment: Alignment) -> Self
+    { self.layout.alignment = alignment; self }
+
+    pub fn field_order(mut self, order: FieldOrder) -> Self
+    { self.layout.field_order = order; self }
+
+    /// Replaces the endianness, alignment and field order set so far.
+    pub fn layout(mut self, layout: Layout) -> Self
+    { self.layout = layout; self }
+
+    pub fn pad_byte(mut self, pad: u8) -> Self
+    { self.pad = pad; self }
+
+    pub fn unique_ids<I: IntoIterator<Item = FourCC>>(mut self, ids: I) -> Self
+    { self.unique = ids.into_iter().collect(); self }
+
+    /// An encoder of chunks into `w` with these options.
+    pub fn build<W: Write>(&self, w: W) -> Encoder<W> {
+        Encoder::new(w)
+            .with_layout(self.layout)
+            .with_pad_byte(self.pad)
+            .with_unique_ids(self.unique.iter().copied())
+    }
+}
+
+impl Default for EncoderBuilder
+{ fn default() -> Self { Self::new() } }
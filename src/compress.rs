@@ -0,0 +1,49 @@
+//! Decompression of the chunk bodies stored compressed by some formats,
+//! to be plugged into `Decoder::map_bodies`.
+use alloc::vec::Vec;
+use crate::FormatError;
+
+/// Expands a body compressed with PackBits, the `ByteRun1` scheme of
+/// ILBM `BODY` chunks. Each run starts with a byte `n`: `0..=127` is
+/// followed by `n + 1` bytes copied as they are, `-127..=-1` by a
+/// byte repeated `1 - n` times, and `-128` is skipped. A run cut short
+/// by the end of `data` is reported as too short.
+/// ```
+/// use iffc::compress::unpack_bits;
+///
+/// let packed = b"\xFE\xAA\x02\x80\x00\x2A\xFD\xAA\x03\x80\x00\x2A\x22\xF7\xAA";
+///
+/// assert_eq!(unpack_bits(packed).unwrap(), [
+///     0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0xAA, 0xAA, 0xAA, 0xAA, 0x80, 0x00,
+///     0x2A, 0x22, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA
+/// ]);
+///
+/// assert!(unpack_bits(b"\x02ab").is_err());
+/// ```
+pub fn unpack_bits(data: &[u8]) -> Result<Vec<u8>, FormatError> {
+    let mut out = Vec::with_capacity(data.len() * 2);
+    let mut i = 0;
+
+    while i < data.len() {
+        let n = data[i] as i8;
+        i += 1;
+
+        let len = match n {
+            -128 => continue,
+            0..=127 => n as usize + 1,
+            _ => 1
+        };
+
+        let run = data.get(i..i + len)
+            .ok_or(FormatError::TooShort { expected: i + len, got: data.len() })?;
+
+        match n {
+            0..=127 => out.extend_from_slice(run),
+            _ => out.resize(out.len() + (1 - n as isize) as usize, run[0])
+        }
+
+        i += len;
+    }
+
+    Ok(out)
+}
@@ -0,0 +1,58 @@
+/// A hash computed over the header and body of each chunk as it's
+/// decoded, see `Decoder::digests`. A fresh one is made with `default`
+/// for every chunk.
+pub trait ChunkDigest: Default {
+    type Output;
+
+    /// Feeds the next bytes of the chunk.
+    fn update(&mut self, data: &[u8]);
+
+    /// The hash of all the bytes fed.
+    fn finish(self) -> Self::Output;
+}
+
+/// CRC-32 with the reflected polynomial `0xEDB88320`, as used by PNG
+/// and zlib, computed incrementally over the pieces of a chunk.
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32(u32);
+
+const TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB88320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+
+        table[n] = c;
+        n += 1;
+    }
+
+    table
+};
+
+impl Crc32 {
+    pub(crate) fn new() -> Self { Self(0xFFFFFFFF) }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        for &b in data
+        { self.0 = TABLE[((self.0 ^ b as u32) & 0xFF) as usize] ^ (self.0 >> 8) }
+    }
+
+    pub(crate) fn finish(self) -> u32 { self.0 ^ 0xFFFFFFFF }
+}
+
+impl Default for Crc32
+{ fn default() -> Self { Self::new() } }
+
+impl ChunkDigest for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) { Crc32::update(self, data) }
+    fn finish(self) -> u32 { Crc32::finish(self) }
+}
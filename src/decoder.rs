@@ -0,0 +1,2081 @@
+use std::io::{self, Read, BufReader, Seek, SeekFrom, ErrorKind};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::borrow::Cow;
+use std::path::Path;
+use std::ops::Range;
+use std::iter::FusedIterator;
+use crate::{Chunk, RawChunk, FourCC, Group, Endianness, Alignment, FieldOrder, DecodeError, ChunkLayout, Layout, DEFAULT_MAX_CHUNK_SIZE};
+use crate::{layout, ids};
+use std::marker::PhantomData;
+use crate::crc::{Crc32, ChunkDigest};
+
+/// A structure which wraps a reader and parses IFF chunks and
+/// behaves like an iterator which yields `Result<Chunk, DecodeError>`
+/// until the reader is exhausted at a chunk boundary. A stream which
+/// ends midway a chunk yields an error instead of ending silently.
+/// 
+/// Any `R: Read` can be wrapped, including trait-objects such as a
+/// `Box<dyn Read>`, and reclaimed afterwards with `into_inner`.
+///
+/// A reader owned elsewhere can be lent to a decoder as `&mut R` (or
+/// `&mut dyn Read`), which holds the borrow for as long as the decoder
+/// lives. Once it's dropped the reader carries on right after the last
+/// chunk decoded:
+/// ```
+/// use std::io::{Cursor, Read};
+/// use iffc::{Decoder, Chunk, FourCC};
+///
+/// let mut r = Cursor::new(b"fmt \x02\x00\x00\x00abdata\x01\x00\x00\x00c\0rest".to_vec());
+///
+/// let mut dec = Decoder::new(&mut r as &mut dyn Read);
+/// assert_eq!(dec.next().unwrap().unwrap(), Chunk(FourCC(*b"fmt "), Box::new(*b"ab")));
+/// assert_eq!(dec.next().unwrap().unwrap(), Chunk(FourCC(*b"data"), Box::new(*b"c")));
+/// drop(dec);
+///
+/// let mut rest = String::new();
+/// r.read_to_string(&mut rest).unwrap();
+/// assert_eq!(rest, "rest");
+/// ```
+/// 
+/// Readers which hand out data in small pieces (pipes, sockets) are
+/// read from until each header and body is complete:
+/// ```
+/// use std::io::{self, Read};
+/// use iffc::{Decoder, Chunk, FourCC};
+/// 
+/// // a reader which yields a single byte per call.
+/// struct Drip(io::Cursor<Vec<u8>>);
+/// 
+/// impl Read for Drip {
+///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+///         let n = buf.len().min(1);
+///         self.0.read(&mut buf[..n])
+///     }
+/// }
+/// 
+/// let inp = Drip(io::Cursor::new(b"fmt \x02\x00\x00\x00abdata\x01\x00\x00\x00c".to_vec()));
+/// let chunks = Decoder::new(inp)
+///     .collect::<Result<Vec<Chunk>, _>>()
+///     .unwrap();
+/// 
+/// assert_eq!(chunks, [
+///     Chunk(FourCC(*b"fmt "), Box::new(*b"ab")),
+///     Chunk(FourCC(*b"data"), Box::new(*b"c"))
+/// ]);
+/// ```
+/// 
+/// A read returning zero bytes is taken as the end of the stream, and
+/// only then, whereas `ErrorKind::Interrupted` is retried. If it comes
+/// right at a chunk boundary the iteration ends cleanly. Anywhere else
+/// it's an error: `UnexpectedEof` inside of a header and `TruncatedBody`
+/// inside of a body. A read handing out the last bytes of the stream is
+/// never mistaken for its end, only the zero-byte read after it is:
+/// ```
+/// use std::io::{self, Read};
+/// use std::collections::VecDeque;
+/// use iffc::{Decoder, DecodeError};
+///
+/// // hands out the given pieces, or `None` for an interruption.
+/// struct Pieces(VecDeque<Option<&'static [u8]>>);
+///
+/// impl Read for Pieces {
+///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+///         match self.0.pop_front() {
+///             Some(Some(p)) => {
+///                 let n = p.len().min(buf.len());
+///                 buf[..n].copy_from_slice(&p[..n]);
+///                 if n < p.len() { self.0.push_front(Some(&p[n..])) }
+///                 Ok(n)
+///             },
+///             Some(None) => Err(io::ErrorKind::Interrupted.into()),
+///             None => Ok(0)
+///         }
+///     }
+/// }
+///
+/// let pieces = |p: &[Option<&'static [u8]>]| Pieces(p.iter().copied().collect());
+///
+/// let whole = pieces(&[Some(b"data\x03"), None, Some(b"\x00\x00\x00ab"), Some(b"c\0")]);
+/// let chunks = Decoder::new(whole).collect::<Vec<_>>();
+/// assert_eq!(chunks.len(), 1);
+/// assert_eq!(&chunks[0].as_ref().unwrap().1[..], b"abc");
+///
+/// let mid_body = pieces(&[Some(b"data\x03\x00\x00\x00"), Some(b"ab")]);
+/// let mut dec = Decoder::new(mid_body);
+/// assert!(matches!(dec.next(), Some(Err(DecodeError::TruncatedBody { expected: 3, got: 2, .. }))));
+///
+/// let mid_header = pieces(&[Some(b"dat")]);
+/// let mut dec = Decoder::new(mid_header);
+/// assert!(matches!(dec.next(), Some(Err(DecodeError::UnexpectedEof { expected: 8, got: 3 }))));
+/// ```
+///
+/// Each header is read into a single 8-byte buffer with plain `read`
+/// calls, never `read_vectored`, so the id and size can't end up split
+/// across buffers by a reader whose vectored reads fill only the first
+/// one, as the default implementation does:
+/// ```
+/// use std::io::{self, Read, IoSliceMut};
+/// use iffc::{Decoder, Chunk, FourCC};
+///
+/// struct Plain<'a>(&'a [u8]);
+///
+/// impl Read for Plain<'_> {
+///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.0.read(buf) }
+///
+///     fn read_vectored(&mut self, _: &mut [IoSliceMut<'_>]) -> io::Result<usize>
+///     { unreachable!("headers are read with `read` alone") }
+/// }
+///
+/// let inp = b"fmt \x02\x00\x00\x00abdata\x01\x00\x00\x00c\0";
+/// assert_eq!(Decoder::new(Plain(inp)).collect::<Result<Vec<Chunk>, _>>().unwrap(), [
+///     Chunk(FourCC(*b"fmt "), Box::new(*b"ab")),
+///     Chunk(FourCC(*b"data"), Box::new(*b"c"))
+/// ]);
+/// ```
+///
+/// Whereas a stream which ends inside of a chunk is reported:
+/// ```
+/// use iffc::{Decoder, DecodeError};
+/// 
+/// let inp = std::io::Cursor::new(b"data\x08\x00\x00\x00abc");
+/// let mut dec = Decoder::new(inp);
+/// 
+/// match dec.next() {
+///     Some(Err(DecodeError::TruncatedBody { id, offset: 0, expected: 8, got: 3 })) if id == "data" => {},
+///     r => panic!("unexpected {:?}", r)
+/// }
+///
+/// // the error tells which chunk was cut short, and where.
+/// let inp = b"fmt \x02\x00\x00\x00abdata\x08\x00\x00\x00abc";
+/// let err = Decoder::new(&inp[..]).nth(1).unwrap().unwrap_err();
+///
+/// assert_eq!(err.to_string(), "truncated body of chunk \"data\" at offset 10 (3 of 8 bytes)");
+/// ```
+/// 
+/// Sizes are read little-endian (RIFF) unless told otherwise:
+/// ```
+/// use iffc::{Decoder, Endianness};
+/// 
+/// let inp = std::io::Cursor::new(b"FORM\x00\x00\x00\x04AIFF");
+/// let mut dec = Decoder::new(inp).with_endianness(Endianness::Big);
+/// 
+/// assert_eq!(&dec.next().unwrap().unwrap().1[..], b"AIFF");
+/// ```
+/// 
+/// Odd-length bodies are followed by a pad byte, which is skipped:
+/// ```
+/// use iffc::{Decoder, Chunk, FourCC};
+/// 
+/// let inp = std::io::Cursor::new(b"abc \x03\x00\x00\x00xyz\0next\x00\x00\x00\x00");
+/// let chunks = Decoder::new(inp)
+///     .collect::<Result<Vec<Chunk>, _>>()
+///     .unwrap();
+/// 
+/// assert_eq!(chunks, [
+///     Chunk(FourCC(*b"abc "), Box::new(*b"xyz")),
+///     Chunk(FourCC(*b"next"), Box::new([]))
+/// ]);
+/// ```
+/// 
+/// Bodies are only allocated as their bytes arrive, and a chunk which
+/// declares a size above `max_chunk_size` is rejected up front:
+/// ```
+/// use iffc::{Decoder, DecodeError};
+/// 
+/// let inp = std::io::Cursor::new(b"data\xFF\xFF\xFF\xFF");
+/// let mut dec = Decoder::new(inp);
+/// 
+/// match dec.next() {
+///     Some(Err(DecodeError::ChunkTooLarge { size: 0xFFFFFFFF, .. })) => {},
+///     r => panic!("unexpected {:?}", r)
+/// }
+/// ```
+pub struct Decoder<R: Read, L: ChunkLayout = Layout> {
+    r: R,
+    layout: L,
+    max_chunk_size: u32,
+    max_chunks: u32,
+    chunks: u32,
+    budget: u64,
+    allocated: u64,
+    strict: bool,
+    resync: bool,
+    skipped: Vec<Range<u64>>,
+    verify: bool,
+    filler: Option<Vec<FourCC>>,
+    padded_sizes: bool,
+    unsized_top: bool,
+    carry: Option<u8>,
+    pos: u64,
+    remainder: Vec<u8>,
+    peeked: Option<(FourCC, u32)>,
+    header: [u8; 8],
+    done: bool,
+    skip: fn(&mut R, u64) -> io::Result<u64>,
+    remaining: fn(&mut R) -> io::Result<Option<u64>>,
+    end: Option<u64>,
+    hint: Option<usize>
+}
+
+/// State of a `Decoder` saved by `Decoder::snapshot`.
+pub(crate) struct Snapshot {
+    pos: u64,
+    peeked: Option<(FourCC, u32)>,
+    header: [u8; 8],
+    chunks: u32,
+    carry: Option<u8>,
+    remainder: Vec<u8>,
+    skipped: usize
+}
+
+/// Chunks which only reserve space or align what follows them, the ones
+/// `Decoder::with_skip_filler` drops unless told otherwise.
+pub(crate) const FILLER_IDS: [FourCC; 3] = [ids::JUNK, ids::PAD, ids::FLLR];
+
+impl<R: Read> Decoder<R> {
+    /// Decodes little-endian (RIFF) chunks from `r`.
+    pub fn new(r: R) -> Self {
+        Self {
+            r,
+            layout: Layout::RIFF,
+            max_chunk_size: DEFAULT_MAX_CHUNK_SIZE,
+            max_chunks: u32::MAX,
+            chunks: 0,
+            budget: u64::MAX,
+            allocated: 0,
+            strict: false,
+            resync: false,
+            skipped: Vec::new(),
+            verify: true,
+            filler: None,
+            padded_sizes: false,
+            unsized_top: false,
+            carry: None,
+            pos: 0,
+            remainder: Vec::new(),
+            peeked: None,
+            header: [0; 8],
+            done: false,
+            skip: discard::<R>,
+            remaining: unknown::<R>,
+            end: None,
+            hint: None
+        }
+    }
+
+    /// Decodes chunks whose size fields are stored in `endianness`.
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self
+    { self.layout.endianness = endianness; self }
+
+    /// Decodes chunks padded to `alignment`, for formats which don't
+    /// pad their chunks at all use `Alignment::None`.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self
+    { self.layout.alignment = alignment; self }
+
+    /// Decodes chunks whose headers put their fields in `order`, such
+    /// as the length-prefixed ones with `FieldOrder::SizeThenId`, with
+    /// no checksum unless the whole layout is set with `with_layout`.
+    /// ```
+    /// use iffc::{Decoder, Encoder, Chunk, FourCC, FieldOrder, Alignment};
+    ///
+    /// let chunks = [Chunk(FourCC(*b"name"), Box::new(*b"odd")), Chunk::empty(*b"end ")];
+    ///
+    /// let mut enc = Encoder::new(Vec::new()).with_field_order(FieldOrder::SizeThenId);
+    /// enc.write_all(chunks.iter().cloned())?;
+    ///
+    /// let out = enc.into_inner();
+    /// assert_eq!(out, b"\x03\x00\x00\x00nameodd\0\x00\x00\x00\x00end ");
+    ///
+    /// let back = Decoder::new(&out[..]).with_field_order(FieldOrder::SizeThenId).into_chunks().unwrap();
+    /// assert_eq!(back, chunks);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn with_field_order(mut self, order: FieldOrder) -> Self
+    { self.layout.field_order = order; self }
+}
+
+impl<R: Read, L: ChunkLayout> Decoder<R, L> {
+    /// Decodes chunks framed as described by `layout`, such as the
+    /// `IffLayout` or the `PngLayout`, see `ChunkLayout`.
+    pub fn with_layout<M: ChunkLayout>(self, layout: M) -> Decoder<R, M> {
+        Decoder {
+            r: self.r,
+            layout,
+            max_chunk_size: self.max_chunk_size,
+            max_chunks: self.max_chunks,
+            chunks: self.chunks,
+            budget: self.budget,
+            allocated: self.allocated,
+            strict: self.strict,
+            resync: self.resync,
+            skipped: self.skipped,
+            verify: self.verify,
+            filler: self.filler,
+            padded_sizes: self.padded_sizes,
+            unsized_top: self.unsized_top,
+            carry: self.carry,
+            pos: self.pos,
+            remainder: self.remainder,
+            peeked: self.peeked,
+            header: self.header,
+            done: self.done,
+            skip: self.skip,
+            remaining: self.remaining,
+            end: self.end,
+            hint: self.hint
+        }
+    }
+
+    /// Rejects chunks declaring a body larger than `max` bytes with a
+    /// `DecodeError::ChunkTooLarge`, 64 MiB by default. Raise it with
+    /// care when reading from untrusted sources.
+    pub fn with_max_chunk_size(mut self, max: u32) -> Self
+    { self.max_chunk_size = max; self }
+
+    /// Fails with a `DecodeError::TooManyChunks` once more than `max`
+    /// chunks have been read, skipped ones included, so that a stream
+    /// of countless empty chunks can't keep the decoder busy forever.
+    /// Unlimited (`u32::MAX`) by default.
+    /// ```
+    /// use iffc::{Decoder, DecodeError};
+    ///
+    /// let inp = b"none\x00\x00\x00\x00".repeat(10);
+    /// let mut dec = Decoder::new(&inp[..]).with_max_chunks(5);
+    ///
+    /// assert_eq!(dec.by_ref().take(5).filter(|c| c.is_ok()).count(), 5);
+    /// assert!(matches!(dec.next(), Some(Err(DecodeError::TooManyChunks { max: 5 }))));
+    /// ```
+    ///
+    /// The chunks nested in the containers a `TreeDecoder` descends into
+    /// count towards the same limit, at every level:
+    /// ```
+    /// use iffc::{Decoder, DecodeError};
+    ///
+    /// let list = [&b"LIST\x2C\x00\x00\x00INFO"[..], &b"none\x00\x00\x00\x00".repeat(5)].concat();
+    /// let inp = list.repeat(3);
+    ///
+    /// assert_eq!(Decoder::new(&inp[..]).with_max_chunks(18).into_tree().unwrap().len(), 3);
+    /// assert!(matches!(Decoder::new(&inp[..]).with_max_chunks(17).into_tree(),
+    ///     Err(DecodeError::TooManyChunks { max: 17 })));
+    /// ```
+    pub fn with_max_chunks(mut self, max: u32) -> Self
+    { self.max_chunks = max; self }
+
+    /// Fails with a `DecodeError::BudgetExceeded`, before allocating
+    /// it, once the bodies read in total would come to more than
+    /// `budget` bytes. Unlike `with_max_chunk_size` this bounds what
+    /// collecting every chunk of a stream takes. Skipped bodies don't
+    /// count. Unlimited by default.
+    /// ```
+    /// use iffc::{Decoder, DecodeError};
+    ///
+    /// let inp = b"data\x04\x00\x00\x00abcd".repeat(3);
+    /// let mut dec = Decoder::new(&inp[..]).with_memory_budget(10);
+    ///
+    /// assert!(dec.next().unwrap().is_ok());
+    /// assert!(dec.next().unwrap().is_ok());
+    /// assert!(matches!(dec.next(), Some(Err(DecodeError::BudgetExceeded { budget: 10 }))));
+    /// ```
+    pub fn with_memory_budget(mut self, budget: u64) -> Self
+    { self.budget = budget; self }
+
+    /// Rejects ids which aren't printable ASCII with a
+    /// `DecodeError::InvalidId` when `strict`, catching a stream which
+    /// lost sync at its first bad header. Off by default.
+    /// ```
+    /// use iffc::{Decoder, Alignment, DecodeError, FourCC};
+    ///
+    /// // padded chunks read as if they weren't, the pad byte ends up
+    /// // as the first byte of the next id.
+    /// let inp = b"abc \x03\x00\x00\x00xyz\0ab\0\x02\x00\x00\x00cd";
+    ///
+    /// let lenient = Decoder::new(&inp[..]).with_alignment(Alignment::None);
+    /// assert_eq!(lenient.map(|c| c.unwrap().0).collect::<Vec<_>>(),
+    ///            [FourCC(*b"abc "), FourCC(*b"\0ab\0")]);
+    ///
+    /// let mut strict = Decoder::new(&inp[..])
+    ///     .with_alignment(Alignment::None)
+    ///     .with_strict(true);
+    ///
+    /// assert!(strict.next().unwrap().is_ok());
+    /// assert!(matches!(strict.next(), Some(Err(DecodeError::InvalidId { offset: 11, .. }))));
+    /// ```
+    pub fn with_strict(mut self, strict: bool) -> Self
+    { self.strict = strict; self }
+
+    /// Recovers from damaged headers when `resync`, rather than failing
+    /// or yielding garbage: a header whose id isn't printable ASCII, or
+    /// whose body would run past the end of the stream, is taken to be
+    /// out of sync and the decoder scans forward byte by byte until a
+    /// plausible header turns up. Every range skipped that way is kept
+    /// in `skipped`. The end of the stream is only known to decoders
+    /// configured `with_seek`. Off by default.
+    /// ```
+    /// use std::io::Cursor;
+    /// use iffc::{Decoder, FourCC};
+    ///
+    /// // a stray byte after the first chunk, then two of them.
+    /// let inp = b"fmt \x02\x00\x00\x00ab\x01\
+    ///             data\x01\x00\x00\x00c\0xy\
+    ///             note\x00\x00\x00\x00";
+    ///
+    /// let mut dec = Decoder::new(Cursor::new(&inp[..])).with_seek().with_resync(true);
+    /// let ids = dec.by_ref().map(|c| c.unwrap().0).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(ids, [FourCC(*b"fmt "), FourCC(*b"data"), FourCC(*b"note")]);
+    /// assert_eq!(dec.skipped(), [10..11, 21..23]);
+    /// ```
+    pub fn with_resync(mut self, resync: bool) -> Self
+    { self.resync = resync; self }
+
+    /// Ranges of offsets skipped to resynchronize, see `with_resync`.
+    pub fn skipped(&self) -> &[Range<u64>] { &self.skipped }
+
+    /// Whether to check the checksum of each chunk, for layouts which
+    /// have one, a mismatch being a `DecodeError::BadChecksum`. On by
+    /// default, turn it off to read the chunks of a corrupt file.
+    /// ```
+    /// use iffc::{Decoder, Encoder, DecodeError, PngLayout};
+    ///
+    /// let chunk = b"\x00\x00\x00\x02abcdxy\x00\x00\x00\x00";
+    /// let mut dec = Decoder::new(&chunk[..]).with_layout(PngLayout);
+    ///
+    /// match dec.next() {
+    ///     Some(Err(DecodeError::BadChecksum { found: 0, .. })) => {},
+    ///     r => panic!("unexpected {:?}", r)
+    /// }
+    ///
+    /// let chunk = Decoder::new(&chunk[..])
+    ///     .with_layout(PngLayout)
+    ///     .with_verify_checksums(false)
+    ///     .next().unwrap().unwrap();
+    ///
+    /// // written back with a freshly computed CRC, which checks out.
+    /// let mut enc = Encoder::new(Vec::new()).with_layout(PngLayout);
+    /// enc.write_chunk(&chunk).unwrap();
+    ///
+    /// let out = enc.into_inner();
+    /// let again = Decoder::new(&out[..]).with_layout(PngLayout).next().unwrap().unwrap();
+    /// assert_eq!(again, chunk);
+    /// ```
+    pub fn with_verify_checksums(mut self, verify: bool) -> Self
+    { self.verify = verify; self }
+
+    /// Whether to drop the chunks which are mere filler, `JUNK`, `PAD `
+    /// and `FLLR` unless `with_filler_ids` says otherwise, rather than
+    /// yield them. Off by default. Their bodies are skipped without
+    /// being kept, or seeked past with `with_seek`.
+    /// ```
+    /// use iffc::{Decoder, Chunk, Encoder, FourCC};
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// enc.write_chunk(&Chunk(FourCC(*b"JUNK"), Box::new([0; 28])))?;
+    /// enc.write_chunk(&Chunk(FourCC(*b"fmt "), Box::new([1; 16])))?;
+    /// enc.write_chunk(&Chunk(FourCC(*b"PAD "), Box::new([0; 3])))?;
+    /// enc.write_chunk(&Chunk(FourCC(*b"data"), Box::new([2; 4])))?;
+    /// enc.write_chunk(&Chunk(FourCC(*b"JUNK"), Box::new([])))?;
+    /// let out = enc.into_inner();
+    ///
+    /// let ids = |dec: Decoder<_>| dec.map(|c| c.unwrap().0).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(ids(Decoder::new(&out[..])).len(), 5);
+    /// assert_eq!(ids(Decoder::new(&out[..]).with_skip_filler(true)),
+    ///     [FourCC(*b"fmt "), FourCC(*b"data")]);
+    ///
+    /// let only_data = Decoder::new(&out[..])
+    ///     .with_skip_filler(true)
+    ///     .with_filler_ids([FourCC(*b"JUNK"), FourCC(*b"PAD "), FourCC(*b"fmt ")]);
+    /// assert_eq!(ids(only_data), [FourCC(*b"data")]);
+    ///
+    /// // peeking and offsets see past filler as well.
+    /// let mut dec = Decoder::new(&out[..]).with_skip_filler(true);
+    /// assert_eq!(dec.peek_id().unwrap(), Some(FourCC(*b"fmt ")));
+    /// assert_eq!(dec.offset(), 36);
+    ///
+    /// let offsets = Decoder::new(&out[..]).with_skip_filler(true).offsets();
+    /// assert_eq!(offsets.map(|c| c.unwrap().0).collect::<Vec<_>>(), [36, 72]);
+    ///
+    /// let mut dec = Decoder::new(&out[..]).with_skip_filler(true)
+    ///     .ordered([(FourCC(*b"PAD "), FourCC(*b"fmt "))]);
+    /// assert!(matches!(dec.next(), Some(Err(iffc::DecodeError::OutOfOrder { offset: 36, .. }))));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn with_skip_filler(mut self, skip: bool) -> Self {
+        self.filler = match (skip, self.filler) {
+            (false, _) => None,
+            (true, Some(ids)) => Some(ids),
+            (true, None) => Some(FILLER_IDS.to_vec())
+        };
+        self
+    }
+
+    /// Ids of the chunks counted as filler by `with_skip_filler`, which
+    /// is turned on by setting them.
+    pub fn with_filler_ids<I: IntoIterator<Item = FourCC>>(mut self, ids: I) -> Self
+    { self.filler = Some(ids.into_iter().collect()); self }
+
+    /// Works around the writers which wrongly count the pad byte of an
+    /// odd body in its size, leaving no pad after it. When the byte
+    /// read as the pad of an odd chunk isn't zero but could start an
+    /// id, and the body itself ends with a zero, that zero is taken as
+    /// the pad and the byte as the start of the next header. Off by
+    /// default, as it misfires on a correct chunk whose body ends with
+    /// a zero followed by a non-zero pad byte, so only turn it on for
+    /// files known to come from such writers. Skipped chunks aren't
+    /// worked around.
+    /// ```
+    /// use iffc::{Decoder, Chunk, FourCC, Endianness};
+    ///
+    /// // the 2-byte `NAME` declared as 3 bytes, its pad included.
+    /// let inp = b"NAME\x00\x00\x00\x03ab\0\
+    ///             AUTH\x00\x00\x00\x02me";
+    ///
+    /// // the `A` is skipped over as the pad, leaving a `UTH\0` chunk.
+    /// let mut strict = Decoder::new(&inp[..]).with_endianness(Endianness::Big);
+    /// assert_eq!(&strict.next().unwrap().unwrap().1[..], b"ab\0");
+    /// assert!(strict.next().unwrap().is_err());
+    ///
+    /// let lenient = Decoder::new(&inp[..])
+    ///     .with_endianness(Endianness::Big)
+    ///     .with_padded_sizes(true);
+    ///
+    /// assert_eq!(lenient.collect::<Result<Vec<Chunk>, _>>().unwrap(), [
+    ///     Chunk(FourCC(*b"NAME"), Box::new(*b"ab")),
+    ///     Chunk(FourCC(*b"AUTH"), Box::new(*b"me"))
+    /// ]);
+    /// ```
+    pub fn with_padded_sizes(mut self, padded: bool) -> Self
+    { self.padded_sizes = padded; self }
+
+    /// Takes a container (see `Group::is_container`) declaring a size
+    /// of 0 or `u32::MAX` to hold the rest of the stream, as left by
+    /// writers which couldn't seek back to fill the size in, such as
+    /// when piping a WAV file. Only the very first chunk of the stream
+    /// is affected, not those following or nested within it, which
+    /// declare their real size even when it isn't. The body read that way
+    /// is still bound by `with_max_chunk_size`. Off by default, since
+    /// it trusts the end of the stream over the size declared.
+    /// ```
+    /// use iffc::{Decoder, Node, Chunk, FourCC};
+    ///
+    /// let inp = b"RIFF\x00\x00\x00\x00WAVE\
+    ///             fmt \x02\x00\x00\x00ab\
+    ///             data\x01\x00\x00\x00c\0";
+    ///
+    /// let tree = Decoder::new(&inp[..]).with_unsized_containers(true).into_tree().unwrap();
+    /// assert_eq!(tree, [Node::Branch { id: FourCC(*b"RIFF"), form_type: FourCC(*b"WAVE"), children: vec![
+    ///     Node::Leaf(Chunk(FourCC(*b"fmt "), Box::new(*b"ab"))),
+    ///     Node::Leaf(Chunk(FourCC(*b"data"), Box::new(*b"c")))
+    /// ]}]);
+    ///
+    /// let mut inp = inp.to_vec();
+    /// inp[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    ///
+    /// let riff = Decoder::new(&inp[..]).with_unsized_containers(true).next().unwrap().unwrap();
+    /// assert_eq!(riff.1.len(), inp.len() - 8);
+    /// assert!(Decoder::new(&inp[..]).next().unwrap().is_err());
+    ///
+    /// // an empty LIST past the first chunk is just that.
+    /// let inp = b"fmt \x02\x00\x00\x00abLIST\x00\x00\x00\x00data\x01\x00\x00\x00c\0";
+    /// let ids = Decoder::new(&inp[..])
+    ///     .with_unsized_containers(true)
+    ///     .map(|c| c.unwrap().0)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(ids, [FourCC(*b"fmt "), FourCC(*b"LIST"), FourCC(*b"data")]);
+    /// ```
+    pub fn with_unsized_containers(mut self, tolerate: bool) -> Self
+    { self.unsized_top = tolerate; self }
+
+    /// Yields only the chunks whose id satisfies `f`. The bodies of the
+    /// other chunks are never buffered, they're read and discarded or
+    /// seeked past if the decoder was configured `with_seek`.
+    /// ```
+    /// use iffc::{Decoder, FourCC};
+    ///
+    /// let inp = b"fmt \x02\x00\x00\x00ab\
+    ///             JUNK\x03\x00\x00\x00xyz\0\
+    ///             LIST\x00\x00\x00\x00\
+    ///             data\x01\x00\x00\x00c\0\
+    ///             note\x00\x00\x00\x00";
+    ///
+    /// let ids = Decoder::new(&inp[..])
+    ///     .filter_id(|id| id == "fmt " || id == "data")
+    ///     .map(|c| c.map(|c| c.0))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(ids, [FourCC(*b"fmt "), FourCC(*b"data")]);
+    /// ```
+    ///
+    /// Once the stream has ended it isn't read from again, as with the
+    /// decoder itself:
+    /// ```
+    /// use std::io::{self, Read};
+    /// use iffc::Decoder;
+    ///
+    /// // a pipe which runs dry once, before more bytes arrive.
+    /// struct Pipe<'a>(bool, &'a [u8]);
+    ///
+    /// impl Read for Pipe<'_> {
+    ///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    ///         if !self.0 { self.0 = true; return Ok(0) }
+    ///         self.1.read(buf)
+    ///     }
+    /// }
+    ///
+    /// let mut chunks = Decoder::new(Pipe(false, b"data\x00\x00\x00\x00")).filter_id(|_| true);
+    /// assert!(chunks.next().is_none());
+    /// assert!(chunks.next().is_none());
+    /// ```
+    pub fn filter_id<F: FnMut(FourCC) -> bool>(self, f: F) -> FilterId<R, F, L>
+    { FilterId { dec: self, f } }
+
+    /// Merges each run of chunks whose id satisfies `f` into the chunk
+    /// before them, their bodies appended to its own, for formats which
+    /// split a body across continuation chunks. A continuation at the
+    /// very start is yielded as it is. An error reading a continuation
+    /// is yielded after the chunk merged so far.
+    /// ```
+    /// use iffc::{Decoder, Chunk, FourCC};
+    ///
+    /// let inp = b"data\x02\x00\x00\x00ab\
+    ///             cont\x03\x00\x00\x00cde\0\
+    ///             cont\x01\x00\x00\x00f\0\
+    ///             note\x00\x00\x00\x00";
+    ///
+    /// let chunks = Decoder::new(&inp[..])
+    ///     .coalesce(|id| id == "cont")
+    ///     .collect::<Result<Vec<Chunk>, _>>().unwrap();
+    ///
+    /// assert_eq!(chunks, [
+    ///     Chunk(FourCC(*b"data"), Box::new(*b"abcdef")),
+    ///     Chunk(FourCC(*b"note"), Box::new([]))
+    /// ]);
+    /// ```
+    pub fn coalesce<F: FnMut(FourCC) -> bool>(self, f: F) -> Coalesce<R, F, L>
+    { Coalesce { dec: self, f, error: None } }
+
+    /// Checks that chunks come in the order required by `rules`, each
+    /// `(a, b)` meaning that a chunk with id `b` may only come after one
+    /// with id `a`, such as `fmt ` before `data` in a `WAVE` file. A `b`
+    /// read before any `a`, even if none follows, is yielded as a
+    /// `DecodeError::OutOfOrder` instead of the chunk.
+    /// ```
+    /// use iffc::{Decoder, Chunk, FourCC, DecodeError};
+    ///
+    /// let rules = [(FourCC(*b"fmt "), FourCC(*b"data"))];
+    ///
+    /// let inp = b"fmt \x02\x00\x00\x00abdata\x01\x00\x00\x00c\0data\x00\x00\x00\x00";
+    /// assert_eq!(Decoder::new(&inp[..]).ordered(rules).count(), 3);
+    ///
+    /// let inp = b"data\x01\x00\x00\x00c\0fmt \x02\x00\x00\x00ab";
+    /// let mut dec = Decoder::new(&inp[..]).ordered(rules);
+    ///
+    /// assert!(matches!(dec.next(), Some(Err(DecodeError::OutOfOrder { offset: 0, .. }))));
+    /// assert_eq!(dec.next().unwrap().unwrap(), Chunk(FourCC(*b"fmt "), Box::new(*b"ab")));
+    /// ```
+    pub fn ordered<I: IntoIterator<Item = (FourCC, FourCC)>>(self, rules: I) -> Ordered<R, L>
+    { Ordered { dec: self, rules: rules.into_iter().collect(), seen: Vec::new() } }
+
+    /// Passes the body of each chunk through `f` along with its id, such
+    /// as to decompress the bodies of some ids. The body itself returned
+    /// borrowed is kept without a copy.
+    /// ```
+    /// use std::borrow::Cow;
+    /// use iffc::{Decoder, Chunk, FourCC, Endianness};
+    /// use iffc::compress::unpack_bits;
+    ///
+    /// let inp = b"BMHD\x00\x00\x00\x01x\0\
+    ///             BODY\x00\x00\x00\x04\xFD\xAA\x00\x01";
+    ///
+    /// let chunks = Decoder::new(&inp[..])
+    ///     .with_endianness(Endianness::Big)
+    ///     .map_bodies(|id, body| match id == "BODY" {
+    ///         true => unpack_bits(body).map_or(Cow::Borrowed(body), Cow::Owned),
+    ///         false => Cow::Borrowed(body)
+    ///     })
+    ///     .collect::<Result<Vec<Chunk>, _>>().unwrap();
+    ///
+    /// assert_eq!(chunks, [
+    ///     Chunk(FourCC(*b"BMHD"), Box::new(*b"x")),
+    ///     Chunk(FourCC(*b"BODY"), Box::new([0xAA, 0xAA, 0xAA, 0xAA, 0x01]))
+    /// ]);
+    /// ```
+    pub fn map_bodies<F>(self, f: F) -> MapBodies<R, F, L>
+        where F: FnMut(FourCC, &[u8]) -> Cow<'_, [u8]>
+    { MapBodies { dec: self, f } }
+
+    /// Reads up to the first chunk with `id` and returns it, the bodies
+    /// of the chunks before it are skipped as with `filter_id`. `None`
+    /// is returned if the stream ends without it, whereas a malformed
+    /// chunk in the way aborts the search with its error.
+    /// ```
+    /// use iffc::{Decoder, FourCC};
+    ///
+    /// let inp = b"fmt \x02\x00\x00\x00ab\
+    ///             JUNK\x03\x00\x00\x00xyz\0\
+    ///             data\x01\x00\x00\x00c\0\
+    ///             bad \x09\x00\x00\x00";
+    ///
+    /// let mut dec = Decoder::new(&inp[..]);
+    ///
+    /// let data = dec.find_chunk(*b"data").unwrap().unwrap();
+    /// assert_eq!(&data.1[..], b"c");
+    ///
+    /// assert!(dec.find_chunk(*b"fmt ").is_err());
+    /// assert!(Decoder::new(&inp[..22]).find_chunk(*b"LIST").unwrap().is_none());
+    ///
+    /// // the end of the stream is where any later search ends as well.
+    /// let mut dec = Decoder::new(&inp[..22]);
+    /// assert!(dec.find_chunk(*b"LIST").unwrap().is_none());
+    /// assert!(dec.find_chunk(*b"fmt ").unwrap().is_none());
+    /// assert!(dec.next().is_none());
+    /// ```
+    pub fn find_chunk<I: Into<FourCC>>(&mut self, id: I) -> Result<Option<Chunk>, DecodeError> {
+        if self.done { return Ok(None) }
+        let id = id.into();
+
+        while let Some((found, size)) = self.read_header()? {
+            if found == id
+            { return self.read_body(found, size).map(Some) }
+
+            self.skip_body(found, size)?;
+        }
+
+        self.done = true;
+        Ok(None)
+    }
+
+    /// Lists the id and size of every chunk left, filler included,
+    /// without keeping any of their bodies: they are discarded, or
+    /// seeked past after `with_seek`. See `scan_nested` for listing the
+    /// chunks within containers as well.
+    /// ```
+    /// use std::io::Cursor;
+    /// use iffc::{Decoder, FourCC};
+    ///
+    /// let inp = b"RIFF\x24\x00\x00\x00WAVE\
+    ///             fmt \x02\x00\x00\x00ab\
+    ///             LIST\x0D\x00\x00\x00INFO\
+    ///             INAM\x01\x00\x00\x00x\0\
+    ///             JUNK\x00\x00\x00\x00\
+    ///             data\x01\x00\x00\x00c\0";
+    ///
+    /// assert_eq!(Decoder::new(&inp[..]).scan_ids()?, [
+    ///     (FourCC(*b"RIFF"), 0x24),
+    ///     (FourCC(*b"JUNK"), 0),
+    ///     (FourCC(*b"data"), 1)
+    /// ]);
+    ///
+    /// assert_eq!(Decoder::new(&inp[..]).scan_nested()?, [
+    ///     (0, FourCC(*b"RIFF"), 0x24),
+    ///     (1, FourCC(*b"fmt "), 2),
+    ///     (1, FourCC(*b"LIST"), 0x0D),
+    ///     (2, FourCC(*b"INAM"), 1),
+    ///     (0, FourCC(*b"JUNK"), 0),
+    ///     (0, FourCC(*b"data"), 1)
+    /// ]);
+    ///
+    /// let mut seeking = Decoder::new(Cursor::new(&inp[..])).with_seek();
+    /// assert_eq!(seeking.scan_nested()?, Decoder::new(&inp[..]).scan_nested()?);
+    /// # Ok::<(), iffc::DecodeError>(())
+    /// ```
+    pub fn scan_ids(&mut self) -> Result<Vec<(FourCC, u32)>, DecodeError> {
+        let mut ids = Vec::new();
+
+        while let Some((id, size)) = self.read_header()? {
+            self.skip_body(id, size)?;
+            ids.push((id, size));
+        }
+
+        Ok(ids)
+    }
+
+    /// Like `scan_ids`, but descends into the containers known to
+    /// `Group::is_container`, listing each chunk along with how deeply
+    /// it's nested, `0` being the level of this decoder. Containers
+    /// nested deeper than 64 levels fail with a
+    /// `DecodeError::DepthExceeded`, as with a `TreeDecoder`.
+    pub fn scan_nested(&mut self) -> Result<Vec<(usize, FourCC, u32)>, DecodeError> {
+        let mut ids = Vec::new();
+        self.scan_level(None, 0, &mut ids)?;
+        Ok(ids)
+    }
+
+    /// Lists the chunks up to the offset `end`, or the end of the
+    /// stream, into `ids`, see `scan_nested`.
+    fn scan_level(&mut self, end: Option<u64>, depth: usize, ids: &mut Vec<(usize, FourCC, u32)>)
+        -> Result<(), DecodeError>
+    {
+        const MAX_DEPTH: usize = 64;
+
+        while end.map_or(true, |end| self.pos < end) {
+            let (id, size) = match self.read_header()? {
+                Some(header) => header,
+                None => break
+            };
+
+            ids.push((depth, id, size));
+
+            if !Group::is_container(id) || size < 4 {
+                self.skip_body(id, size)?;
+                continue
+            }
+
+            if depth + 1 > MAX_DEPTH
+            { return Err(DecodeError::DepthExceeded { max: MAX_DEPTH }) }
+
+            let body_end = self.pos + size as u64;
+
+            let mut form_type = [0u8; 4];
+            let n = read_upto(&mut self.r, &mut form_type)?;
+            self.pos += n as u64;
+
+            if n != 4
+            { return Err(DecodeError::UnexpectedEof { expected: 4, got: n }) }
+
+            self.scan_level(Some(body_end), depth + 1, ids)?;
+
+            // whatever is left of the container and its padding, which
+            // the padding of an odd last child may have been read as.
+            let next = body_end + self.tail_len(size as usize) as u64;
+            self.pos += (self.skip)(&mut self.r, next.saturating_sub(self.pos))?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the header of the container at the top of a file, such as
+    /// its `RIFF` or `FORM` chunk, along with its form type, so that
+    /// its children are what's iterated over next. Any chunks after the
+    /// container are iterated over as well. Fails with a
+    /// `DecodeError::NotContainer` if the first chunk isn't one of the
+    /// ids known to `Group::is_container`.
+    /// ```
+    /// use iffc::{Decoder, Chunk, FourCC, DecodeError};
+    ///
+    /// let inp = b"RIFF\x18\x00\x00\x00WAVE\
+    ///             fmt \x02\x00\x00\x00ab\
+    ///             data\x01\x00\x00\x00c\0";
+    ///
+    /// let mut dec = Decoder::new(&inp[..]);
+    /// assert_eq!(dec.read_form_type()?, FourCC(*b"WAVE"));
+    ///
+    /// assert_eq!(dec.collect::<Result<Vec<Chunk>, _>>()?, [
+    ///     Chunk(FourCC(*b"fmt "), Box::new(*b"ab")),
+    ///     Chunk(FourCC(*b"data"), Box::new(*b"c"))
+    /// ]);
+    ///
+    /// let mut dec = Decoder::new(&inp[12..]);
+    /// assert!(matches!(dec.read_form_type(), Err(DecodeError::NotContainer { offset: 0, .. })));
+    /// # Ok::<(), DecodeError>(())
+    /// ```
+    pub fn read_form_type(&mut self) -> Result<FourCC, DecodeError> {
+        let offset = self.offset();
+
+        let (id, size) = self.read_header()?
+            .ok_or(DecodeError::UnexpectedEof { expected: 8, got: 0 })?;
+
+        if !Group::is_container(id)
+        { return Err(DecodeError::NotContainer { id, offset }) }
+
+        if size < 4
+        { return Err(DecodeError::UnexpectedEof { expected: 4, got: size as usize }) }
+
+        let mut form_type = [0u8; 4];
+        let n = read_upto(&mut self.r, &mut form_type)?;
+        self.pos += n as u64;
+
+        if n != 4
+        { return Err(DecodeError::UnexpectedEof { expected: 4, got: n }) }
+
+        Ok(FourCC(form_type))
+    }
+
+    /// Number of bytes consumed from the reader so far, headers, bodies
+    /// and pad bytes included. Between chunks this is the offset of the
+    /// next one, relative to where the decoder started reading.
+    pub fn offset(&self) -> u64
+    { self.pos - if self.peeked.is_some() { 8 } else { 0 } - self.carry.is_some() as u64 }
+
+    /// Id of the next chunk, without reading past its header. The
+    /// header is kept for the next call to `next` (or any other method
+    /// reading chunks), rather than being read again. `None` if the
+    /// reader is exhausted at a chunk boundary. Filler is skipped over
+    /// first when `with_skip_filler` is on, as `next` would.
+    /// ```
+    /// use iffc::{Decoder, FourCC};
+    ///
+    /// let inp = b"fmt \x02\x00\x00\x00abdata\x01\x00\x00\x00c";
+    /// let mut dec = Decoder::new(&inp[..]);
+    ///
+    /// assert_eq!(dec.peek_id().unwrap(), Some(FourCC(*b"fmt ")));
+    /// assert_eq!(dec.peek_id().unwrap(), Some(FourCC(*b"fmt ")));
+    /// assert_eq!(dec.offset(), 0);
+    ///
+    /// assert_eq!(&dec.next().unwrap().unwrap().1[..], b"ab");
+    /// assert_eq!(dec.peek_id().unwrap(), Some(FourCC(*b"data")));
+    /// assert_eq!(&dec.next().unwrap().unwrap().1[..], b"c");
+    /// assert_eq!(dec.peek_id().unwrap(), None);
+    /// ```
+    pub fn peek_id(&mut self) -> Result<Option<FourCC>, DecodeError> {
+        if self.peeked.is_none()
+        { self.peeked = self.read_kept_header()? }
+
+        Ok(self.peeked.map(|(id, _)| id))
+    }
+
+    /// Reads the next chunk, telling apart a stream ending at a chunk
+    /// boundary, `Ok(None)`, from one ending within a header, which is
+    /// a `DecodeError::UnexpectedEof` holding how many of its 8 bytes
+    /// were there. The `Iterator` impl is a thin wrapper around it.
+    /// ```
+    /// use iffc::{Decoder, Chunk, FourCC, DecodeError};
+    ///
+    /// let inp = b"data\x01\x00\x00\x00a\0note\x02\x00";
+    ///
+    /// let mut dec = Decoder::new(&inp[..]);
+    /// assert_eq!(dec.try_next()?, Some(Chunk(FourCC(*b"data"), Box::new(*b"a"))));
+    /// assert!(matches!(dec.try_next(), Err(DecodeError::UnexpectedEof { expected: 8, got: 6 })));
+    ///
+    /// let mut dec = Decoder::new(&inp[..10]);
+    /// assert!(dec.try_next()?.is_some());
+    /// assert_eq!(dec.try_next()?, None);
+    ///
+    /// assert_eq!(Decoder::new(&b""[..]).try_next()?, None);
+    /// # Ok::<(), DecodeError>(())
+    /// ```
+    pub fn try_next(&mut self) -> Result<Option<Chunk>, DecodeError> {
+        if self.done { return Ok(None) }
+
+        let chunk = self.read_chunk();
+        self.done = matches!(chunk, Ok(None));
+        self.hint = self.hint.map(|n| n.saturating_sub(1));
+        chunk
+    }
+
+    /// Reads every chunk left, or fails with the first error, the limits
+    /// set with `with_max_chunks`, `with_memory_budget` and the like
+    /// applying as they do while iterating.
+    /// ```
+    /// use iffc::{Decoder, Chunk, FourCC, DecodeError};
+    ///
+    /// let inp = b"fmt \x02\x00\x00\x00abdata\x01\x00\x00\x00c\0note\x00\x00\x00\x00";
+    ///
+    /// assert_eq!(Decoder::new(&inp[..]).into_chunks()?, [
+    ///     Chunk(FourCC(*b"fmt "), Box::new(*b"ab")),
+    ///     Chunk(FourCC(*b"data"), Box::new(*b"c")),
+    ///     Chunk::empty(*b"note")
+    /// ]);
+    ///
+    /// let limited = Decoder::new(&inp[..]).with_max_chunks(2).into_chunks();
+    /// assert!(matches!(limited, Err(DecodeError::TooManyChunks { max: 2 })));
+    /// # Ok::<(), DecodeError>(())
+    /// ```
+    pub fn into_chunks(self) -> Result<Vec<Chunk>, DecodeError>
+    { self.collect() }
+
+    /// Like `try_next`, but reads the body into `buf` rather than a new
+    /// allocation, returning its id. `buf` is cleared first and keeps
+    /// its capacity, so that a loop over many small chunks reuses the
+    /// same buffer for all of them.
+    /// ```
+    /// use iffc::{Decoder, Encoder, Chunk, FourCC, DecodeError};
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// for n in 0..100_000u32 {
+    ///     enc.write_chunk(&Chunk(FourCC(*b"smpl"), Box::new(n.to_le_bytes())))?;
+    /// }
+    /// let out = enc.into_inner();
+    ///
+    /// let mut dec = Decoder::new(&out[..]);
+    /// let mut buf = Vec::with_capacity(4);
+    /// let at = buf.as_ptr();
+    /// let mut n = 0u32;
+    ///
+    /// while let Some(id) = dec.next_into(&mut buf)? {
+    ///     assert_eq!((id, &buf[..]), (FourCC(*b"smpl"), &n.to_le_bytes()[..]));
+    ///     n += 1;
+    /// }
+    ///
+    /// // every body went through the one allocation.
+    /// assert_eq!((n, buf.as_ptr()), (100_000, at));
+    /// assert_eq!(dec.next_into(&mut buf)?, None);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn next_into(&mut self, buf: &mut Vec<u8>) -> Result<Option<FourCC>, DecodeError> {
+        if self.done { return Ok(None) }
+
+        let id = match self.read_kept_header() {
+            Ok(Some((id, size))) => self.read_body_into(id, size, true, buf).map(|()| Some(id)),
+            header => header.map(|_| None)
+        };
+
+        self.done = matches!(id, Ok(None));
+        self.hint = self.hint.map(|n| n.saturating_sub(1));
+        id
+    }
+
+    /// Bytes of the incomplete chunk at which the stream ended, once
+    /// reading it failed with a `DecodeError::UnexpectedEof` or a
+    /// `DecodeError::TruncatedBody`. It holds the fragment of a header,
+    /// or a header and the part of its body which was there, and is
+    /// empty as long as the stream ended at a chunk boundary.
+    /// ```
+    /// use iffc::{Decoder, DecodeError};
+    ///
+    /// let mut dec = Decoder::new(&b"data\x01\x00\x00\x00c\0ab"[..]);
+    ///
+    /// assert!(dec.next().unwrap().is_ok());
+    /// assert!(dec.remainder().is_empty());
+    ///
+    /// assert!(matches!(dec.next(), Some(Err(DecodeError::UnexpectedEof { got: 2, .. }))));
+    /// assert_eq!(dec.remainder(), b"ab");
+    ///
+    /// let mut dec = Decoder::new(&b"data\x08\x00\x00\x00abc"[..]);
+    ///
+    /// assert!(dec.next().unwrap().is_err());
+    /// assert_eq!(dec.remainder(), b"data\x08\x00\x00\x00abc");
+    /// ```
+    pub fn remainder(&self) -> &[u8] { &self.remainder }
+
+    /// Pairs each chunk with the offset of its header.
+    /// ```
+    /// use iffc::Decoder;
+    ///
+    /// let inp = b"abc \x03\x00\x00\x00xyz\0\
+    ///             next\x00\x00\x00\x00\
+    ///             last\x02\x00\x00\x00ab";
+    ///
+    /// let offsets = Decoder::new(&inp[..])
+    ///     .offsets()
+    ///     .map(|c| c.map(|(off, _)| off))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(offsets, [0, 8 + 3 + 1, 8 + 3 + 1 + 8]);
+    /// ```
+    ///
+    /// Which makes for tracing a stream chunk by chunk, with the logger
+    /// of one's choice in place of the `Vec` here:
+    /// ```
+    /// use iffc::Decoder;
+    ///
+    /// let inp = b"RIFF\x04\x00\x00\x00WAVEfmt \x10\x00\x00\x00\
+    ///             \x01\x00\x02\x00\x44\xAC\x00\x00\x10\xB1\x02\x00\x04\x00\x10\x00";
+    /// let mut log = Vec::new();
+    ///
+    /// for chunk in Decoder::new(&inp[..]).offsets() {
+    ///     let (offset, chunk) = chunk.unwrap();
+    ///     log.push(format!("read chunk {} at offset {:#04X}", chunk, offset));
+    /// }
+    ///
+    /// assert_eq!(log, [
+    ///     "read chunk \"RIFF\" (4 bytes) at offset 0x00",
+    ///     "read chunk \"fmt \" (16 bytes) at offset 0x0C"
+    /// ]);
+    /// ```
+    pub fn offsets(self) -> Offsets<R, L> { Offsets(self) }
+
+    /// Iterates over the chunks along with the pad bytes found after
+    /// each of them, for `Encoder::write_raw` to reproduce the stream
+    /// exactly. Filler chunks aren't skipped, but anything skipped over
+    /// to resync is lost.
+    /// ```
+    /// use iffc::{Decoder, Encoder, RawChunk};
+    ///
+    /// // a space for a pad byte, and the last one left out.
+    /// let inp = b"abc \x03\x00\x00\x00xyz data\x02\x00\x00\x00deodd \x01\x00\x00\x00z";
+    ///
+    /// let chunks = Decoder::new(&inp[..]).raw().collect::<Result<Vec<RawChunk>, _>>()?;
+    /// assert_eq!(&chunks[0].pad[..], b" ");
+    /// assert!(chunks[2].pad.is_empty());
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// for c in &chunks { enc.write_raw(c)?; }
+    /// assert_eq!(enc.into_inner(), &inp[..]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn raw(self) -> RawChunks<R, L> { RawChunks(self) }
+
+    /// Iterates over the chunks along with a digest of each, computed
+    /// by `H` over the header and body as they're stored, checksum and
+    /// padding excluded. Unlike the checksums of `Checksum::Crc32`, the
+    /// digests aren't stored in the file, such as when comparing them
+    /// against a separate manifest.
+    /// ```
+    /// use iffc::{Decoder, Crc32, ChunkDigest, Chunk, FourCC};
+    ///
+    /// let inp = b"data\x03\x00\x00\x00abc\0note\x00\x00\x00\x00";
+    /// let digests = Decoder::new(&inp[..]).digests::<Crc32>()
+    ///     .map(|c| c.map(|(chunk, crc)| (chunk.0, crc)))
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// assert_eq!(digests, [(FourCC(*b"data"), 0xFF2C8BA9), (FourCC(*b"note"), 0x1CDCE606)]);
+    ///
+    /// // the header as stored, even when the size it declares isn't the body's.
+    /// let inp = b"RIFF\x00\x00\x00\x00WAVEdata\x00\x00\x00\x00";
+    /// let (riff, crc) = Decoder::new(&inp[..]).with_unsized_containers(true)
+    ///     .digests::<Crc32>().next().unwrap()?;
+    ///
+    /// let mut expected = Crc32::default();
+    /// ChunkDigest::update(&mut expected, &inp[..]);
+    /// assert_eq!((riff.1.len(), crc), (12, ChunkDigest::finish(expected)));
+    /// # Ok::<(), iffc::DecodeError>(())
+    /// ```
+    pub fn digests<H: ChunkDigest>(self) -> Digests<R, L, H>
+    { Digests(self, PhantomData) }
+
+    /// Decodes the chunks held in the next `len` bytes only, such as the
+    /// body of a container, after which this decoder carries on from the
+    /// end of the region. A chunk crossing the end of the region is an
+    /// error, except for the pad byte of the last one, which is then
+    /// left to whatever follows the region. The region is only consumed
+    /// as far as it's iterated over.
+    /// ```
+    /// use iffc::{Decoder, Chunk, FourCC, DecodeError};
+    ///
+    /// let inp = b"head\x02\x00\x00\x00hi\
+    ///             INAM\x02\x00\x00\x00ab\
+    ///             ICMT\x01\x00\x00\x00c\0\
+    ///             next\x00\x00\x00\x00";
+    ///
+    /// let mut dec = Decoder::new(&inp[..]);
+    /// assert!(dec.next().unwrap()?.id() == "head");
+    ///
+    /// let region = dec.take_region(20).collect::<Result<Vec<Chunk>, _>>()?;
+    /// assert_eq!(region, [
+    ///     Chunk(FourCC(*b"INAM"), Box::new(*b"ab")),
+    ///     Chunk(FourCC(*b"ICMT"), Box::new(*b"c"))
+    /// ]);
+    ///
+    /// assert!(dec.next().unwrap()?.id() == "next");
+    ///
+    /// // a chunk running past the region.
+    /// let mut dec = Decoder::new(&inp[10..]);
+    /// let mut region = dec.take_region(15);
+    /// assert!(region.next().unwrap().is_ok());
+    /// assert!(matches!(region.next(), Some(Err(DecodeError::UnexpectedEof { expected: 8, got: 5 }))));
+    /// assert!(region.next().is_none());
+    /// # Ok::<(), DecodeError>(())
+    /// ```
+    pub fn take_region(&mut self, len: usize) -> RegionDecoder<'_, R, L>
+    { RegionDecoder { dec: self, left: len as u64, failed: false } }
+
+    /// Unwraps the underlying reader, positioned right after the last
+    /// chunk which was decoded.
+    /// ```
+    /// use std::io::{Cursor, Read};
+    /// use iffc::Decoder;
+    /// 
+    /// let mut dec = Decoder::new(Cursor::new(b"data\x00\x00\x00\x00rest"));
+    /// dec.next();
+    /// 
+    /// let mut rest = String::new();
+    /// dec.into_inner().read_to_string(&mut rest).unwrap();
+    /// assert_eq!(rest, "rest");
+    /// ```
+    pub fn into_inner(self) -> R { self.r }
+
+    /// Mutable reference to the underlying reader. The decoder never
+    /// reads further than the chunk it yields, its pad byte included,
+    /// so the reader can be read from by hand between chunks. Only a
+    /// header looked at by `peek_id` is read ahead of time. The bytes
+    /// read by hand aren't counted by `offset`.
+    /// ```
+    /// use std::io::Read;
+    /// use iffc::{Decoder, Chunk, FourCC};
+    ///
+    /// let inp = b"FORM\x00\x00\x00\x16ILBM\
+    ///             BMHD\x01\x00\x00\x00x\0\
+    ///             CMAP\x00\x00\x00\x00\
+    ///             trailer";
+    ///
+    /// let mut dec = Decoder::new(&inp[..]);
+    ///
+    /// let mut preamble = [0u8; 12];
+    /// dec.get_mut().read_exact(&mut preamble)?;
+    /// assert_eq!(&preamble[8..], b"ILBM");
+    ///
+    /// assert_eq!(dec.next().unwrap()?, Chunk(FourCC(*b"BMHD"), Box::new(*b"x")));
+    /// assert_eq!(dec.next().unwrap()?, Chunk(FourCC(*b"CMAP"), Box::new([])));
+    ///
+    /// let mut trailer = [0u8; 4];
+    /// dec.get_mut().read_exact(&mut trailer)?;
+    /// assert_eq!(&trailer, b"trai");
+    /// # Ok::<(), iffc::DecodeError>(())
+    /// ```
+    pub fn get_mut(&mut self) -> &mut R { &mut self.r }
+}
+
+/// Reads into `buf` until it is full or the reader reaches its end,
+/// returning how many bytes were read. Unlike `read_exact` this tells
+/// a clean end-of-stream (`0`) apart from a partially filled buffer.
+pub(crate) fn read_upto<R: Read + ?Sized>(r: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut n = 0;
+
+    while n < buf.len() {
+        match r.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(s) => n += s,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {},
+            Err(e) => return Err(e)
+        }
+    }
+
+    Ok(n)
+}
+
+impl<R: Read, L: ChunkLayout> Decoder<R, L> {
+    /// Decoder for the sub-chunks held in `body`, configured the same
+    /// way as this one. Sub-chunks can't be larger than their parent,
+    /// so no limit is put on their size. They count towards the same
+    /// `max_chunks`, the parent picking up the count where it's left.
+    pub(crate) fn sub_decoder<'a>(&self, body: &'a [u8]) -> Decoder<&'a [u8], L> {
+        Decoder {
+            r: body,
+            layout: self.layout,
+            max_chunk_size: u32::MAX,
+            max_chunks: self.max_chunks,
+            chunks: self.chunks,
+            budget: u64::MAX,
+            allocated: 0,
+            strict: self.strict,
+            resync: self.resync,
+            skipped: Vec::new(),
+            verify: self.verify,
+            filler: self.filler.clone(),
+            padded_sizes: self.padded_sizes,
+            unsized_top: false,
+            carry: None,
+            pos: 0,
+            remainder: Vec::new(),
+            peeked: None,
+            header: [0; 8],
+            done: false,
+            skip: discard::<&[u8]>,
+            remaining: |r| Ok(Some(r.len() as u64)),
+            end: None,
+            hint: None
+        }
+    }
+
+    /// Reads the id and size of the next chunk, `None` if the reader
+    /// is exhausted at a chunk boundary.
+    pub(crate) fn read_header(&mut self) -> Result<Option<(FourCC, u32)>, DecodeError> {
+        if let Some(header) = self.peeked.take()
+        { return Ok(Some(header)) }
+
+        let mut header = [0u8; 8];
+
+        // the first byte may have been read as a pad, see `with_padded_sizes`.
+        let carried = self.carry.take().map(|b| header[0] = b).is_some() as usize;
+
+        let n = carried + read_upto(&mut self.r, &mut header[carried..])?;
+        let offset = self.pos - carried as u64;
+        self.pos += (n - carried) as u64;
+
+        match n {
+            0 => return Ok(None),
+            8 => {},
+            n => {
+                self.remainder = header[..n].to_vec();
+                return Err(DecodeError::UnexpectedEof { expected: 8, got: n })
+            }
+        }
+
+        if self.chunks >= self.max_chunks
+        { return Err(DecodeError::TooManyChunks { max: self.max_chunks }) }
+
+        self.chunks += 1;
+
+        let (mut id, mut size) = layout::parse_header(&self.layout, &header);
+
+        if self.resync {
+            let start = offset;
+            let mut offset = offset;
+
+            while !self.plausible(id, size)? {
+                header.copy_within(1.., 0);
+                offset += 1;
+
+                if read_upto(&mut self.r, &mut header[7..])? == 0 {
+                    self.skipped.push(start..offset);
+                    self.remainder = header[..7].to_vec();
+                    return Err(DecodeError::UnexpectedEof { expected: 8, got: 7 })
+                }
+
+                self.pos += 1;
+                (id, size) = layout::parse_header(&self.layout, &header);
+            }
+
+            if offset != start
+            { self.skipped.push(start..offset) }
+        }
+
+        if self.strict && !id.is_printable()
+        { return Err(DecodeError::InvalidId { id, offset }) }
+
+        self.header = header;
+        Ok(Some((id, size)))
+    }
+
+    /// Whether a header with `id` and `size`, the end of which was just
+    /// read, looks like a real one, see `with_resync`.
+    fn plausible(&mut self, id: FourCC, size: u32) -> io::Result<bool> {
+        if !id.is_printable() { return Ok(false) }
+        Ok(self.stream_end()?.map_or(true, |end| self.pos + size as u64 <= end))
+    }
+
+    /// Offset at which the stream ends, if it can be told, see
+    /// `with_seek`. Found out once, then kept.
+    fn stream_end(&mut self) -> io::Result<Option<u64>> {
+        if self.end.is_none()
+        { self.end = (self.remaining)(&mut self.r)?.map(|n| self.pos + n) }
+
+        Ok(self.end)
+    }
+
+    /// Number of bytes which follow a body of `len` bytes, that is its
+    /// checksum if the layout has one and the pad bytes.
+    pub(crate) fn tail_len(&self, len: usize) -> usize
+    { layout::trailer_len(&self.layout) + self.layout.alignment().padding(len) }
+
+    /// Number of chunks read so far, against `with_max_chunks`.
+    pub(crate) fn chunk_count(&self) -> u32 { self.chunks }
+
+    /// Carries on counting from `n` chunks, for when they were read by
+    /// a `sub_decoder`.
+    pub(crate) fn set_chunk_count(&mut self, n: u32) { self.chunks = n }
+
+    /// The state which walking over headers changes, for it to be put
+    /// back with `restore` once the reader is sought back.
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pos: self.pos,
+            peeked: self.peeked,
+            header: self.header,
+            chunks: self.chunks,
+            carry: self.carry,
+            remainder: self.remainder.clone(),
+            skipped: self.skipped.len()
+        }
+    }
+
+    /// Puts back the state saved by `snapshot`.
+    pub(crate) fn restore(&mut self, s: Snapshot) {
+        self.pos = s.pos;
+        self.peeked = s.peeked;
+        self.header = s.header;
+        self.chunks = s.chunks;
+        self.carry = s.carry;
+        self.remainder = s.remainder;
+        self.skipped.truncate(s.skipped);
+    }
+
+    /// Moves the offset reported by `offset` to `pos`, for when the
+    /// reader was sought to where it is at that offset.
+    pub(crate) fn set_offset(&mut self, pos: u64) { self.pos = pos }
+
+    /// Reads the next chunk, `None` if the reader is exhausted at a
+    /// chunk boundary.
+    fn read_chunk(&mut self) -> Result<Option<Chunk>, DecodeError> {
+        match self.read_kept_header()? {
+            Some((id, size)) => self.read_body(id, size).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    /// Offset of the next chunk which isn't filler, skipping the filler
+    /// before it, so that it's not the filler's offset which is told.
+    fn kept_offset(&mut self) -> Result<u64, DecodeError> {
+        if !self.done && self.filler.is_some()
+        { self.peek_id()?; }
+
+        Ok(self.offset())
+    }
+
+    /// Reads the header of the next chunk which isn't filler, skipping
+    /// the bodies of those which are.
+    fn read_kept_header(&mut self) -> Result<Option<(FourCC, u32)>, DecodeError> {
+        while let Some((id, size)) = self.read_header()? {
+            if self.filler.as_ref().is_some_and(|ids| ids.contains(&id)) {
+                self.skip_body(id, size)?;
+                continue
+            }
+
+            return Ok(Some((id, size)))
+        }
+
+        Ok(None)
+    }
+
+    /// Skips over a body of `size` bytes and its padding, either by
+    /// discarding it or seeking past it.
+    pub(crate) fn skip_body(&mut self, id: FourCC, size: u32) -> Result<(), DecodeError> {
+        let offset = self.pos - 8;
+        let n = (self.skip)(&mut self.r, size as u64)?;
+        self.pos += n;
+
+        if n != size as u64
+        { return Err(DecodeError::TruncatedBody { id, offset, expected: size, got: n as usize }) }
+
+        let tail = self.tail_len(size as usize) as u64;
+        self.pos += (self.skip)(&mut self.r, tail)?;
+
+        Ok(())
+    }
+
+    /// Reads a body of `size` bytes and its padding, following a header
+    /// with `id`.
+    pub(crate) fn read_body(&mut self, id: FourCC, size: u32) -> Result<Chunk, DecodeError>
+    { self.read_body_padded(id, size, true) }
+
+    /// Reads the rest of the stream as the body of a container whose
+    /// size wasn't filled in, see `with_unsized_containers`.
+    fn read_unsized(&mut self, data: &mut Vec<u8>) -> Result<(), DecodeError> {
+        (&mut self.r).take(self.max_chunk_size as u64 + 1).read_to_end(data)?;
+        self.pos += data.len() as u64;
+
+        if data.len() > self.max_chunk_size as usize {
+            let size = u32::try_from(data.len()).unwrap_or(u32::MAX);
+            return Err(DecodeError::ChunkTooLarge { size, max: self.max_chunk_size })
+        }
+
+        if self.allocated.saturating_add(data.len() as u64) > self.budget
+        { return Err(DecodeError::BudgetExceeded { budget: self.budget }) }
+
+        self.allocated += data.len() as u64;
+        Ok(())
+    }
+
+    /// Like `read_body`, but leaves the padding unread unless `pad`.
+    fn read_body_padded(&mut self, id: FourCC, size: u32, pad: bool) -> Result<Chunk, DecodeError> {
+        let mut data = Vec::new();
+        self.read_body_into(id, size, pad, &mut data)?;
+        Ok(Chunk(id, data.into_boxed_slice()))
+    }
+
+    /// Like `read_body_padded`, but reads the body into `data`, which
+    /// is cleared first.
+    fn read_body_into(&mut self, id: FourCC, size: u32, pad: bool, data: &mut Vec<u8>) -> Result<(), DecodeError> {
+        data.clear();
+
+        // the header just read being the first of the stream.
+        if self.unsized_top && self.pos == 8 && (size == 0 || size == u32::MAX) && Group::is_container(id)
+        { return self.read_unsized(data) }
+
+        if size > self.max_chunk_size
+        { return Err(DecodeError::ChunkTooLarge { size, max: self.max_chunk_size }) }
+
+        // a body running past the end of a stream whose length is known
+        // is reported before any of it is read.
+        if let Some(end) = self.stream_end()? {
+            if self.pos + size as u64 > end {
+                let got = end.saturating_sub(self.pos) as usize;
+                self.remainder = layout::make_header(&self.layout, id, size).to_vec();
+
+                return Err(DecodeError::TruncatedBody { id, offset: self.pos - 8, expected: size, got })
+            }
+        }
+
+        if self.allocated.saturating_add(size as u64) > self.budget
+        { return Err(DecodeError::BudgetExceeded { budget: self.budget }) }
+
+        if usize::try_from(size).is_err()
+        { return Err(DecodeError::SizeOverflow { id, size }) }
+
+        self.allocated += size as u64;
+        let offset = self.pos - 8;
+
+        // grows along with the bytes actually read, so a size field
+        // larger than the stream doesn't allocate all of it up front.
+        (&mut self.r).take(size as u64).read_to_end(data)?;
+        self.pos += data.len() as u64;
+
+        if data.len() != size as usize {
+            let got = data.len();
+
+            self.remainder = layout::make_header(&self.layout, id, size).to_vec();
+            self.remainder.append(data);
+
+            return Err(DecodeError::TruncatedBody { id, offset, expected: size, got })
+        }
+
+        let trailer = layout::trailer_len(&self.layout);
+        let mut crc = [0u8; 4];
+
+        let n = read_upto(&mut self.r, &mut crc[..trailer])?;
+        self.pos += n as u64;
+
+        if n != trailer
+        { return Err(DecodeError::UnexpectedEof { expected: trailer, got: n }) }
+
+        if trailer != 0 && self.verify {
+            let mut expected = Crc32::new();
+            expected.update(&id.0);
+            expected.update(data);
+
+            let expected = expected.finish();
+            let found = Endianness::Big.read_u32(crc);
+
+            if expected != found
+            { return Err(DecodeError::BadChecksum { expected, found }) }
+        }
+
+        // the pad byte of the very last chunk is often left out by
+        // writers, so a stream ending there is not considered truncated.
+        if pad {
+            let mut pad = [0u8; 3];
+            let n = read_upto(&mut self.r, &mut pad[..self.layout.alignment().padding(data.len())])?;
+            self.pos += n as u64;
+
+            if self.padded_sizes && n == 1 && pad[0].is_ascii_graphic() && data.last() == Some(&0) {
+                data.pop();
+                self.carry = Some(pad[0]);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Decoder<BufReader<R>> {
+    /// Decodes chunks from `r` through a `BufReader`, so that the header
+    /// and body of small chunks come out of its buffer rather than each
+    /// costing a call on `r`. Worthwhile for unbuffered readers like a
+    /// `File`, the reader is reclaimed with `into_inner().into_inner()`.
+    /// ```
+    /// use std::io::{self, Read};
+    /// use iffc::{Decoder, Encoded, Chunk, FourCC};
+    ///
+    /// // a reader which counts the calls made on it.
+    /// struct Counted<'a>(&'a [u8], usize);
+    ///
+    /// impl Read for Counted<'_> {
+    ///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+    ///     { self.1 += 1; self.0.read(buf) }
+    /// }
+    ///
+    /// let inp = (0..1000u16)
+    ///     .map(|n| Chunk(FourCC(*b"note"), Box::new(n.to_le_bytes())))
+    ///     .collect::<io::Result<Encoded>>().unwrap().0;
+    ///
+    /// let mut direct = Decoder::new(Counted(&inp, 0));
+    /// assert_eq!(direct.by_ref().count(), 1000);
+    /// assert!(direct.into_inner().1 >= 2000);
+    ///
+    /// let mut buffered = Decoder::buffered(Counted(&inp, 0));
+    /// assert_eq!(buffered.by_ref().count(), 1000);
+    /// assert!(buffered.into_inner().into_inner().1 < 10);
+    /// ```
+    pub fn buffered(r: R) -> Self
+    { Decoder::new(BufReader::new(r)) }
+}
+
+impl Decoder<BufReader<File>> {
+    /// Opens the file at `path` and decodes its chunks through a
+    /// `BufReader`, see `buffered`.
+    /// ```no_run
+    /// use iffc::Decoder;
+    ///
+    /// for chunk in Decoder::open("song.wav")? {
+    ///     let chunk = chunk.expect("malformed chunk");
+    ///     println!("{}", chunk);
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self>
+    { File::open(path).map(Decoder::buffered) }
+}
+
+impl<R: Read + Seek, L: ChunkLayout> Decoder<R, L> {
+    /// Skips unwanted bodies by seeking past them rather than reading
+    /// them, see `filter_id`. Since seeking past the end of a stream
+    /// succeeds, truncated bodies aren't detected when skipped.
+    ///
+    /// Bodies which are read are checked against the length of the
+    /// stream instead, so one declaring more bytes than are left fails
+    /// with a `DecodeError::TruncatedBody` before any of it is read or
+    /// allocated. Its `remainder` then only holds the header.
+    /// ```
+    /// use std::io::Cursor;
+    /// use iffc::{Decoder, DecodeError};
+    ///
+    /// let inp = Cursor::new(b"JUNK\x03\x00\x00\x00xyz\0data\x01\x00\x00\x00c");
+    /// let mut data = Decoder::new(inp).with_seek().filter_id(|id| id == "data");
+    ///
+    /// assert_eq!(&data.next().unwrap().unwrap().1[..], b"c");
+    ///
+    /// // a 1 GiB body, of which only 10 bytes follow.
+    /// let mut inp = b"data\x00\x00\x00\x40".to_vec();
+    /// inp.extend_from_slice(&[0; 10]);
+    ///
+    /// let mut dec = Decoder::new(Cursor::new(&inp[..])).with_seek().with_max_chunk_size(u32::MAX);
+    /// assert!(matches!(dec.next(), Some(Err(DecodeError::TruncatedBody { expected: 0x4000_0000, got: 10, .. }))));
+    /// assert_eq!(dec.remainder(), b"data\x00\x00\x00\x40");
+    /// assert_eq!(dec.get_mut().position(), 8);
+    /// ```
+    pub fn with_seek(mut self) -> Self
+    { self.skip = seek::<R>; self.remaining = remaining::<R>; self }
+
+    /// Seeks the reader to `offset` and carries on decoding from there,
+    /// as if the stream started with a chunk at it, for instance when
+    /// the user of a repair tool picks where the next chunk begins after
+    /// an error. The peeked header, the `remainder` and the pad byte and
+    /// hint which were pending are dropped, and decoding resumes even if
+    /// it had stopped. The limits of `with_max_chunks` and
+    /// `with_memory_budget` start over, and `skipped` is cleared, as they
+    /// only concern the stream read from now on. `offset` then reports
+    /// offsets from the start of the reader.
+    /// ```
+    /// use std::io::Cursor;
+    /// use iffc::{Decoder, Chunk, FourCC};
+    ///
+    /// let inp = Cursor::new(b"abc \x03\x00\x00\x00xyz\0data\x01\x00\x00\x00c\0");
+    /// let mut dec = Decoder::new(inp);
+    ///
+    /// let first = dec.by_ref().collect::<Result<Vec<Chunk>, _>>()?;
+    /// assert_eq!(first.len(), 2);
+    /// assert!(dec.next().is_none());
+    ///
+    /// dec.resume_at(0)?;
+    /// assert_eq!(dec.offset(), 0);
+    /// assert_eq!(dec.by_ref().collect::<Result<Vec<Chunk>, _>>()?, first);
+    ///
+    /// dec.resume_at(12)?;
+    /// assert_eq!(dec.peek_id()?, Some(FourCC(*b"data")));
+    /// assert_eq!(dec.offset(), 12);
+    ///
+    /// // the chunks read before don't count against the limit.
+    /// let mut dec = Decoder::new(Cursor::new(b"none\x00\x00\x00\x00".repeat(2))).with_max_chunks(2);
+    /// assert_eq!(dec.by_ref().filter(|c| c.is_ok()).count(), 2);
+    ///
+    /// dec.resume_at(0)?;
+    /// assert_eq!(dec.by_ref().filter(|c| c.is_ok()).count(), 2);
+    /// # Ok::<(), iffc::DecodeError>(())
+    /// ```
+    pub fn resume_at(&mut self, offset: u64) -> Result<(), DecodeError> {
+        self.r.seek(SeekFrom::Start(offset))?;
+
+        self.pos = offset;
+        self.peeked = None;
+        self.remainder.clear();
+        self.carry = None;
+        self.hint = None;
+        self.end = None;
+        self.done = false;
+        self.chunks = 0;
+        self.allocated = 0;
+        self.skipped.clear();
+        Ok(())
+    }
+
+    /// Counts the chunks which are left by seeking over their headers,
+    /// then seeks back to where it started. From then on `size_hint`
+    /// tells exactly how many chunks are left, so that collecting them
+    /// allocates once. Filler chunks dropped by `with_skip_filler` aren't
+    /// counted. The hint is ignored while resyncing, as the chunks which
+    /// will be found can't be told in advance. The reader is restored
+    /// even if a malformed header stops the count.
+    /// ```
+    /// use std::io::Cursor;
+    /// use iffc::{Decoder, Encoder, Chunk, FourCC};
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// for i in 0..100u8 {
+    ///     enc.write_chunk(&Chunk(FourCC(*b"seq "), Box::new([i])))?;
+    /// }
+    ///
+    /// let mut dec = Decoder::new(Cursor::new(enc.into_inner()));
+    /// assert_eq!(dec.size_hint(), (0, None));
+    ///
+    /// assert_eq!(dec.prescan()?, 100);
+    /// dec.next();
+    /// assert_eq!(dec.size_hint(), (99, Some(99)));
+    ///
+    /// let chunks = dec.collect::<Vec<_>>();
+    /// assert_eq!(chunks.len(), 99);
+    /// assert_eq!(chunks.capacity(), 99);
+    ///
+    /// // what's found while counting is only reported once read for real.
+    /// let inp = Cursor::new(b"JUNK\x02\x00\x00\x00ab\0\0data\x01\x00\x00\x00c\0");
+    /// let mut dec = Decoder::new(inp).with_skip_filler(true).with_resync(true);
+    ///
+    /// assert_eq!(dec.prescan()?, 1);
+    /// assert!(dec.skipped().is_empty());
+    ///
+    /// assert_eq!(dec.by_ref().count(), 1);
+    /// assert_eq!(dec.skipped(), [10..12]);
+    /// # Ok::<(), iffc::DecodeError>(())
+    /// ```
+    pub fn prescan(&mut self) -> Result<usize, DecodeError> {
+        let start = self.r.stream_position()?;
+        let state = self.snapshot();
+
+        let count = self.count_headers();
+
+        self.r.seek(SeekFrom::Start(start))?;
+        self.restore(state);
+
+        let count = count?;
+        self.hint = Some(count);
+        Ok(count)
+    }
+
+    /// Walks over the headers left, counting those which will be yielded.
+    fn count_headers(&mut self) -> Result<usize, DecodeError> {
+        let mut count = 0;
+
+        while let Some((id, size)) = self.read_header()? {
+            if !self.filler.as_ref().is_some_and(|ids| ids.contains(&id))
+            { count += 1 }
+
+            let len = size as u64 + self.tail_len(size as usize) as u64;
+            self.r.seek(SeekFrom::Current(len as i64))?;
+            self.pos += len;
+        }
+
+        Ok(count)
+    }
+}
+
+/// Skips `n` bytes by reading and dropping them, returns how many were
+/// there before the end of the stream.
+fn discard<R: Read>(r: &mut R, n: u64) -> io::Result<u64>
+{ io::copy(&mut r.take(n), &mut io::sink()) }
+
+/// Number of bytes left in a stream, which can't be told without
+/// seeking.
+fn unknown<R: Read>(_: &mut R) -> io::Result<Option<u64>> { Ok(None) }
+
+/// Number of bytes between the position of `r` and its end.
+fn remaining<R: Read + Seek>(r: &mut R) -> io::Result<Option<u64>> {
+    let pos = r.stream_position()?;
+    let end = r.seek(SeekFrom::End(0))?;
+
+    r.seek(SeekFrom::Start(pos))?;
+    Ok(Some(end.saturating_sub(pos)))
+}
+
+/// Skips `n` bytes by seeking past them.
+fn seek<R: Read + Seek>(r: &mut R, n: u64) -> io::Result<u64>
+{ r.seek(SeekFrom::Current(n as i64)).map(|_| n) }
+
+impl<R: Read, L: ChunkLayout> Iterator for Decoder<R, L> {
+    type Item = Result<Chunk, DecodeError>;
+
+    /// Reads the next chunk, ending the iteration once the reader is
+    /// exhausted at a chunk boundary. The reader isn't read from again
+    /// after that.
+    /// ```
+    /// use std::cell::Cell;
+    /// use std::io::{self, Read};
+    /// use iffc::Decoder;
+    ///
+    /// // a reader which counts the calls made on it.
+    /// struct Counted<'a>(&'a [u8], &'a Cell<usize>);
+    ///
+    /// impl Read for Counted<'_> {
+    ///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+    ///     { self.1.set(self.1.get() + 1); self.0.read(buf) }
+    /// }
+    ///
+    /// let calls = Cell::new(0);
+    /// let mut dec = Decoder::new(Counted(b"data\x00\x00\x00\x00", &calls));
+    ///
+    /// assert!(dec.next().is_some());
+    /// assert!(dec.next().is_none());
+    ///
+    /// let before = calls.get();
+    /// assert!((0..3).all(|_| dec.next().is_none()));
+    /// assert_eq!(calls.get(), before);
+    /// ```
+    fn next(&mut self) -> Option<Self::Item>
+    { self.try_next().transpose() }
+
+    /// Exactly the number of chunks left if the decoder was prescanned
+    /// (see `prescan`), otherwise unknown.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.hint {
+            _ if self.done => (0, Some(0)),
+            Some(n) if !self.resync => (n, Some(n)),
+            _ => (0, None)
+        }
+    }
+}
+
+impl<R: Read, L: ChunkLayout> FusedIterator for Decoder<R, L> {}
+
+/// Iterator over the chunks of a `Decoder` along with their digests,
+/// see `Decoder::digests`.
+pub struct Digests<R: Read, L: ChunkLayout, H>(Decoder<R, L>, PhantomData<H>);
+
+impl<R: Read, L: ChunkLayout, H: ChunkDigest> Iterator for Digests<R, L, H> {
+    type Item = Result<(Chunk, H::Output), DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = match self.0.next()? {
+            Ok(chunk) => chunk,
+            Err(e) => return Some(Err(e))
+        };
+
+        let mut digest = H::default();
+        digest.update(&self.0.header);
+        digest.update(&chunk.1);
+
+        Some(Ok((chunk, digest.finish())))
+    }
+}
+
+impl<R: Read, L: ChunkLayout, H: ChunkDigest> FusedIterator for Digests<R, L, H> {}
+
+/// Iterator over the chunks of a `Decoder` along with their padding,
+/// see `Decoder::raw`.
+pub struct RawChunks<R: Read, L: ChunkLayout = Layout>(Decoder<R, L>);
+
+impl<R: Read, L: ChunkLayout> RawChunks<R, L> {
+    fn read_chunk(&mut self) -> Result<Option<RawChunk>, DecodeError> {
+        let dec = &mut self.0;
+
+        let (id, size) = match dec.read_header()? {
+            Some(header) => header,
+            None => return Ok(None)
+        };
+
+        let chunk = dec.read_body_padded(id, size, false)?;
+
+        let mut pad = [0u8; 3];
+        let n = read_upto(&mut dec.r, &mut pad[..dec.layout.alignment().padding(size as usize)])?;
+        dec.pos += n as u64;
+
+        Ok(Some(RawChunk { chunk, pad: pad[..n].into() }))
+    }
+}
+
+impl<R: Read, L: ChunkLayout> Iterator for RawChunks<R, L> {
+    type Item = Result<RawChunk, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.done { return None }
+
+        let chunk = self.read_chunk().transpose();
+        self.0.done = chunk.is_none();
+        chunk
+    }
+}
+
+impl<R: Read, L: ChunkLayout> FusedIterator for RawChunks<R, L> {}
+
+/// Iterator over the chunks of a `Decoder` paired with their offsets,
+/// see `Decoder::offsets`.
+pub struct Offsets<R: Read, L: ChunkLayout = Layout>(Decoder<R, L>);
+
+impl<R: Read, L: ChunkLayout> FusedIterator for Offsets<R, L> {}
+
+impl<R: Read, L: ChunkLayout> Iterator for Offsets<R, L> {
+    type Item = Result<(u64, Chunk), DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let off = match self.0.kept_offset() {
+            Ok(off) => off,
+            Err(e) => return Some(Err(e))
+        };
+
+        self.0.next().map(|c| c.map(|c| (off, c)))
+    }
+}
+
+/// Iterator over the chunks within a region of a `Decoder`, see
+/// `Decoder::take_region`. An error ends the iteration.
+pub struct RegionDecoder<'a, R: Read, L: ChunkLayout = Layout> {
+    dec: &'a mut Decoder<R, L>,
+    left: u64,
+    failed: bool
+}
+
+impl<R: Read, L: ChunkLayout> RegionDecoder<'_, R, L> {
+    /// Number of bytes of the region which haven't been read yet.
+    pub fn remaining(&self) -> u64 { self.left }
+
+    fn read_chunk(&mut self) -> Result<Chunk, DecodeError> {
+        if self.left < 8
+        { return Err(DecodeError::UnexpectedEof { expected: 8, got: self.left as usize }) }
+
+        let start = self.dec.offset();
+
+        let (id, size) = match self.dec.read_header()? {
+            Some(header) => header,
+            None => return Err(DecodeError::UnexpectedEof { expected: 8, got: 0 })
+        };
+
+        let trailer = layout::trailer_len(&self.dec.layout) as u64;
+        let framed = 8 + size as u64 + trailer;
+
+        if framed > self.left {
+            let got = (self.left - 8).min(size as u64) as usize;
+            return Err(DecodeError::TruncatedBody { id, offset: start, expected: size, got })
+        }
+
+        let padded = framed + self.dec.layout.alignment().padding(size as usize) as u64 <= self.left;
+        let chunk = self.dec.read_body_padded(id, size, padded)?;
+
+        self.left = self.left.saturating_sub(self.dec.offset() - start);
+        Ok(chunk)
+    }
+}
+
+impl<R: Read, L: ChunkLayout> Iterator for RegionDecoder<'_, R, L> {
+    type Item = Result<Chunk, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.left == 0 { return None }
+
+        let chunk = self.read_chunk();
+        self.failed = chunk.is_err();
+        Some(chunk)
+    }
+}
+
+impl<R: Read, L: ChunkLayout> FusedIterator for RegionDecoder<'_, R, L> {}
+
+/// Iterator over the chunks of a `Decoder` with their bodies passed
+/// through a function, see `Decoder::map_bodies`.
+pub struct MapBodies<R: Read, F, L: ChunkLayout = Layout> {
+    dec: Decoder<R, L>,
+    f: F
+}
+
+impl<R: Read, F, L: ChunkLayout> Iterator for MapBodies<R, F, L>
+    where F: FnMut(FourCC, &[u8]) -> Cow<'_, [u8]>
+{
+    type Item = Result<Chunk, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = match self.dec.next()? {
+            Ok(chunk) => chunk,
+            Err(e) => return Some(Err(e))
+        };
+
+        let body = match (self.f)(chunk.0, &chunk.1) {
+            Cow::Borrowed(body) if std::ptr::eq(body, &chunk.1[..]) => None,
+            body => Some(body.into_owned().into_boxed_slice())
+        };
+
+        Some(Ok(match body {
+            Some(body) => Chunk(chunk.0, body),
+            None => chunk
+        }))
+    }
+}
+
+/// Iterator merging continuation chunks into the one before them, see
+/// `Decoder::coalesce`.
+pub struct Coalesce<R: Read, F, L: ChunkLayout = Layout> {
+    dec: Decoder<R, L>,
+    f: F,
+    error: Option<DecodeError>
+}
+
+impl<R: Read, F: FnMut(FourCC) -> bool, L: ChunkLayout> Coalesce<R, F, L> {
+    /// Appends the bodies of the continuations following the chunk
+    /// yielded last onto `body`.
+    fn append(&mut self, body: &mut Vec<u8>) -> Result<(), DecodeError> {
+        while self.dec.peek_id()?.is_some_and(|id| (self.f)(id)) {
+            match self.dec.try_next()? {
+                Some(c) => body.extend_from_slice(&c.1),
+                None => break
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read, F: FnMut(FourCC) -> bool, L: ChunkLayout> Iterator for Coalesce<R, F, L> {
+    type Item = Result<Chunk, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take()
+        { return Some(Err(e)) }
+
+        let chunk = match self.dec.try_next() {
+            Ok(chunk) => chunk?,
+            Err(e) => return Some(Err(e))
+        };
+
+        let mut body = chunk.1.into_vec();
+        self.error = self.append(&mut body).err();
+
+        Some(Ok(Chunk(chunk.0, body.into_boxed_slice())))
+    }
+}
+
+/// Iterator checking the order of chunks, see `Decoder::ordered`.
+pub struct Ordered<R: Read, L: ChunkLayout = Layout> {
+    dec: Decoder<R, L>,
+    rules: Vec<(FourCC, FourCC)>,
+    seen: Vec<FourCC>
+}
+
+impl<R: Read, L: ChunkLayout> Iterator for Ordered<R, L> {
+    type Item = Result<Chunk, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = match self.dec.kept_offset() {
+            Ok(offset) => offset,
+            Err(e) => return Some(Err(e))
+        };
+
+        let chunk = match self.dec.next()? {
+            Ok(chunk) => chunk,
+            Err(e) => return Some(Err(e))
+        };
+
+        let broken = self.rules.iter()
+            .find(|&&(before, after)| after == chunk.0 && !self.seen.contains(&before));
+
+        if !self.seen.contains(&chunk.0)
+        { self.seen.push(chunk.0) }
+
+        match broken {
+            Some(&(before, after)) => Some(Err(DecodeError::OutOfOrder { before, after, offset })),
+            None => Some(Ok(chunk))
+        }
+    }
+}
+
+impl<R: Read, L: ChunkLayout> FusedIterator for Ordered<R, L> {}
+
+/// Iterator over the chunks of a `Decoder` whose id satisfies a
+/// predicate, see `Decoder::filter_id`.
+pub struct FilterId<R: Read, F, L: ChunkLayout = Layout> {
+    dec: Decoder<R, L>,
+    f: F
+}
+
+impl<R: Read, F: FnMut(FourCC) -> bool, L: ChunkLayout> Iterator for FilterId<R, F, L> {
+    type Item = Result<Chunk, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.dec.done { return None }
+
+        loop {
+            let (id, size) = match self.dec.read_header() {
+                Ok(Some(h)) => h,
+                Ok(None) => { self.dec.done = true; return None },
+                Err(e) => return Some(Err(e))
+            };
+
+            if (self.f)(id)
+            { return Some(self.dec.read_body(id, size)) }
+
+            if let Err(e) = self.dec.skip_body(id, size)
+            { return Some(Err(e)) }
+        }
+    }
+}
+
+impl<R: Read, F: FnMut(FourCC) -> bool, L: ChunkLayout> FusedIterator for FilterId<R, F, L> {}
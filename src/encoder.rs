@@ -0,0 +1,979 @@
+use std::io::{self, Read, Write, BufWriter, Seek, SeekFrom};
+use std::fs::File;
+use std::path::Path;
+use std::ops::{Shl, Deref, DerefMut};
+use std::iter::FromIterator;
+use std::convert::TryFrom;
+use crate::{Chunk, RawChunk, BorrowedChunk, Group, FourCC, Endianness, Alignment, ChunkLayout, Layout, FieldOrder};
+use crate::layout;
+use crate::crc::Crc32;
+
+/// A structure which wraps a writer and writes IFF chunks to it,
+/// by using `<<` (shift-left) with an RHS of type `IFFChunk`, also
+/// that operand can be chained. Any `W: Write` can be wrapped and
+/// reclaimed afterwards with `into_inner`.
+/// 
+/// Short writes are retried until the entire chunk has been written:
+/// ```
+/// use std::io::{self, Read, Write, Seek, SeekFrom};
+/// use iffc::{Encoder, Chunk, FourCC};
+/// 
+/// // a writer which accepts at most 3 bytes per call.
+/// struct Trickle(Vec<u8>);
+/// 
+/// impl Write for Trickle {
+///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+///         let n = buf.len().min(3);
+///         self.0.extend_from_slice(&buf[..n]);
+///         Ok(n)
+///     }
+/// 
+///     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+/// }
+/// 
+/// let enc = Encoder::new(Trickle(Vec::new()));
+/// let enc = (enc << Chunk(FourCC(*b"RIFF"), Box::new(*b"WAVE"))).unwrap();
+/// 
+/// assert_eq!(enc.into_inner().0, b"RIFF\x04\x00\x00\x00WAVE");
+/// ```
+/// 
+/// Odd-length bodies are followed by a pad byte, so that what is
+/// written can be read back by the `Decoder`:
+/// ```
+/// use iffc::{Encoder, Decoder, Chunk, FourCC};
+/// 
+/// let enc = Encoder::new(Vec::new());
+/// let enc = (enc << Chunk(FourCC(*b"abc "), Box::new(*b"xyz"))).unwrap();
+/// let enc = (enc << Chunk(FourCC(*b"next"), Box::new(*b"ab"))).unwrap();
+/// 
+/// let out = enc.into_inner();
+/// assert_eq!(&out[..12], b"abc \x03\x00\x00\x00xyz\0");
+/// 
+/// let chunks = Decoder::new(&out[..])
+///     .collect::<Result<Vec<Chunk>, _>>()
+///     .unwrap();
+/// 
+/// assert_eq!(chunks, [
+///     Chunk(FourCC(*b"abc "), Box::new(*b"xyz")),
+///     Chunk(FourCC(*b"next"), Box::new(*b"ab"))
+/// ]);
+/// ```
+pub struct Encoder<W: Write, L: ChunkLayout = Layout> {
+    w: Option<W>,
+    layout: L,
+    pad: u8,
+    unique: Vec<FourCC>,
+    seen: Vec<Vec<FourCC>>,
+    error: Option<io::Error>
+}
+
+impl<W: Write> Encoder<W> {
+    /// Encodes little-endian (RIFF) chunks into `w`.
+    pub fn new(w: W) -> Self {
+        Self {
+            w: Some(w),
+            layout: Layout::RIFF,
+            pad: 0,
+            unique: Vec::new(),
+            seen: vec![Vec::new()],
+            error: None
+        }
+    }
+
+    /// Encodes chunks whose size fields are stored in `endianness`.
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self
+    { self.layout.endianness = endianness; self }
+
+    /// Pads each chunk to `alignment` with zero bytes.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self
+    { self.layout.alignment = alignment; self }
+
+    /// Puts the fields of each header in `order`, see
+    /// `Decoder::with_field_order`.
+    pub fn with_field_order(mut self, order: FieldOrder) -> Self
+    { self.layout.field_order = order; self }
+}
+
+impl Encoder<BufWriter<File>> {
+    /// Creates (or truncates) the file at `path` and encodes chunks into
+    /// it through a `BufWriter`. Call `flush` once done, since errors
+    /// flushing the buffer on drop go unnoticed.
+    /// ```
+    /// use iffc::{Encoder, Decoder, Chunk, FourCC};
+    ///
+    /// let path = std::env::temp_dir().join("iffc-create.riff");
+    ///
+    /// let mut enc = Encoder::create(&path)?;
+    /// enc.write_chunk(&Chunk(FourCC(*b"data"), Box::new(*b"abc")))?;
+    /// enc.flush()?;
+    ///
+    /// let chunks = Decoder::open(&path)?.collect::<Result<Vec<Chunk>, _>>().unwrap();
+    /// assert_eq!(chunks, [Chunk(FourCC(*b"data"), Box::new(*b"abc"))]);
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self>
+    { File::create(path).map(|f| Encoder::new(BufWriter::new(f))) }
+}
+
+impl<W: Write, L: ChunkLayout> Encoder<W, L> {
+    /// Encodes chunks framed as described by `layout`, see `ChunkLayout`.
+    /// Checksums required by the layout are computed as chunks are
+    /// written.
+    pub fn with_layout<M: ChunkLayout>(mut self, layout: M) -> Encoder<W, M>
+    {
+        Encoder {
+            w: self.w.take(),
+            layout,
+            pad: self.pad,
+            unique: std::mem::take(&mut self.unique),
+            seen: std::mem::take(&mut self.seen),
+            error: self.error.take()
+        }
+    }
+
+    /// Pads chunks with `pad` rather than NUL bytes, some tools pad
+    /// with spaces (`0x20`) instead. Decoders skip pad bytes whatever
+    /// their value is.
+    /// ```
+    /// use iffc::{Encoder, Decoder, Chunk, FourCC};
+    ///
+    /// let chunk = Chunk(FourCC(*b"abc "), Box::new(*b"xyz"));
+    ///
+    /// let mut enc = Encoder::new(Vec::new()).with_pad_byte(b' ');
+    /// enc.write_chunk(&chunk).unwrap();
+    ///
+    /// let out = enc.into_inner();
+    /// assert_eq!(out, b"abc \x03\x00\x00\x00xyz ");
+    /// assert_eq!(Decoder::new(&out[..]).next().unwrap().unwrap(), chunk);
+    /// ```
+    pub fn with_pad_byte(mut self, pad: u8) -> Self
+    { self.pad = pad; self }
+
+    /// Refuses to write a chunk with one of the `ids` if one was already
+    /// written at the same level, either at the top or within the same
+    /// container, as formats such as WAV only allow a single `fmt ` and
+    /// `data` chunk. The second one fails with `InvalidInput` and isn't
+    /// written. Any id may be repeated by default, as IFF allows.
+    /// ```
+    /// use iffc::{Encoder, Chunk, FourCC};
+    ///
+    /// let fmt = Chunk(FourCC(*b"fmt "), Box::new([0; 16]));
+    /// let note = Chunk(FourCC(*b"note"), Box::new([]));
+    ///
+    /// let mut enc = Encoder::new(Vec::new())
+    ///     .with_unique_ids([FourCC(*b"fmt "), FourCC(*b"data")]);
+    ///
+    /// enc.write_chunk(&fmt)?;
+    /// enc.write_chunk(&note)?;
+    /// enc.write_chunk(&note)?;
+    ///
+    /// let err = enc.write_chunk(&fmt).unwrap_err();
+    /// assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    /// assert_eq!(enc.into_inner().len(), 24 + 8 + 8);
+    ///
+    /// // an id is only taken once its chunk is written, and the level of a
+    /// // container dropped unfinished is left along with it.
+    /// use std::io::{self, Cursor, Write};
+    ///
+    /// struct FailsOnce(Vec<u8>, bool);
+    ///
+    /// impl Write for FailsOnce {
+    ///     fn write(&mut self, b: &[u8]) -> io::Result<usize> {
+    ///         if !self.1 { self.1 = true; return Err(io::ErrorKind::Other.into()) }
+    ///         self.0.write(b)
+    ///     }
+    ///     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// }
+    ///
+    /// let mut enc = Encoder::new(FailsOnce(Vec::new(), false)).with_unique_ids([FourCC(*b"fmt ")]);
+    /// assert!(enc.write_chunk(&fmt).is_err());
+    /// enc.write_chunk(&fmt)?;
+    ///
+    /// let mut enc = Encoder::new(Cursor::new(Vec::new())).with_unique_ids([FourCC(*b"fmt ")]);
+    /// enc.write_chunk(&fmt)?;
+    /// drop(enc.begin_group(FourCC(*b"LIST"), FourCC(*b"INFO"))?);
+    /// assert!(enc.write_chunk(&fmt).is_err());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn with_unique_ids<I: IntoIterator<Item = FourCC>>(mut self, ids: I) -> Self
+    { self.unique = ids.into_iter().collect(); self }
+
+    /// Fails if a chunk with `id` is meant to be unique and was already
+    /// written at the current level.
+    fn check_unique(&self, id: FourCC) -> io::Result<()> {
+        let level = self.seen.last().expect("encoder without a top level");
+
+        if self.unique.contains(&id) && level.contains(&id) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("duplicate {:?} chunk", id)))
+        }
+
+        Ok(())
+    }
+
+    /// Records a chunk with `id` as written at the current level, once
+    /// it was, see `check_unique`.
+    fn mark_written(&mut self, id: FourCC) {
+        if self.unique.contains(&id)
+        { self.seen.last_mut().expect("encoder without a top level").push(id) }
+    }
+
+    /// Checks and records `id` at once, for chunks not actually written.
+    fn claim(&mut self, id: FourCC) -> io::Result<()> {
+        self.check_unique(id)?;
+        self.mark_written(id);
+        Ok(())
+    }
+
+    /// Unwraps the underlying writer, without flushing it.
+    pub fn into_inner(mut self) -> W
+    { self.w.take().expect("writer taken out of the encoder") }
+
+    /// Flushes the underlying writer and unwraps it, so that nothing is
+    /// left behind in a buffering writer. Fails like `flush`, in which
+    /// case the writer is dropped.
+    /// ```
+    /// use std::io::{BufWriter, Cursor};
+    /// use iffc::{Encoder, Chunk, FourCC};
+    ///
+    /// let mut enc = Encoder::new(BufWriter::new(Cursor::new(Vec::new())));
+    /// enc.write_chunk(&Chunk(FourCC(*b"data"), Box::new(*b"abc")))?;
+    ///
+    /// let w = enc.finish()?;
+    /// assert!(w.buffer().is_empty());
+    /// assert_eq!(w.get_ref().get_ref(), b"data\x03\x00\x00\x00abc\x00");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.into_inner())
+    }
+
+    /// Writes the chunk's id, its size, the body and pad bytes if any.
+    /// Each segment is written with `write_all`, so writers that only
+    /// accept a few bytes per call still receive the whole chunk. On
+    /// failure the writer may hold a partial chunk. Bodies larger than
+    /// `u32::MAX` bytes are refused with `ErrorKind::InvalidInput`,
+    /// rather than having their size truncated.
+    /// ```
+    /// use iffc::{Encoder, Decoder, Chunk, FourCC};
+    /// 
+    /// let mut enc = Encoder::new(Vec::new());
+    /// for i in 0..1000u32 {
+    ///     enc.write_chunk(&Chunk(FourCC(*b"seq "), Box::new(i.to_le_bytes())))?;
+    /// }
+    /// 
+    /// let out = enc.into_inner();
+    /// assert_eq!(out.len(), 1000 * 12);
+    /// assert_eq!(Decoder::new(&out[..]).count(), 1000);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_chunk(&mut self, chunk: &Chunk) -> io::Result<()> {
+        let body = chunk.checked_body()?;
+        self.check_unique(chunk.0)?;
+        write_framed(writer(&mut self.w), chunk.0, body, &self.layout, self.pad)?;
+
+        self.mark_written(chunk.0);
+        Ok(())
+    }
+
+    /// Writes a chunk whose body is borrowed, byte-identical to writing
+    /// the same `Chunk` with `write_chunk` but without having to move
+    /// the body into a `Box` of its own first.
+    /// ```
+    /// use iffc::{Encoder, Chunk, BorrowedChunk, FourCC};
+    ///
+    /// let buf = [1u8, 2, 3, 4, 5];
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// enc.write_chunk_ref(BorrowedChunk(FourCC(*b"head"), &buf[..2]))?;
+    /// enc.write_chunk_ref(BorrowedChunk(FourCC(*b"tail"), &buf[2..]))?;
+    ///
+    /// let mut owned = Encoder::new(Vec::new());
+    /// owned.write_chunk(&Chunk(FourCC(*b"head"), Box::new([1, 2])))?;
+    /// owned.write_chunk(&Chunk(FourCC(*b"tail"), Box::new([3, 4, 5])))?;
+    ///
+    /// assert_eq!(enc.into_inner(), owned.into_inner());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_chunk_ref(&mut self, chunk: BorrowedChunk<'_>) -> io::Result<()> {
+        self.check_unique(chunk.0)?;
+        write_framed(writer(&mut self.w), chunk.0, chunk.1, &self.layout, self.pad)?;
+
+        self.mark_written(chunk.0);
+        Ok(())
+    }
+
+    /// Writes every chunk of `chunks` in order, returning the number of
+    /// bytes they took up, headers, checksums and padding included.
+    /// Stops at the first chunk failing to be written and returns its
+    /// error, the chunks before it having been written entirely.
+    /// ```
+    /// use iffc::{Encoder, Chunk, FourCC};
+    ///
+    /// let chunks = vec![
+    ///     Chunk(FourCC(*b"fmt "), Box::new([0; 16])),
+    ///     Chunk(FourCC(*b"note"), Box::new(*b"odd")),
+    ///     Chunk(FourCC(*b"data"), Box::new([]))
+    /// ];
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// let len = enc.write_all(chunks)?;
+    ///
+    /// assert_eq!(len, (8 + 16) + (8 + 3 + 1) + 8);
+    /// assert_eq!(enc.into_inner().len(), len);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_all<I: IntoIterator<Item = Chunk>>(&mut self, chunks: I) -> io::Result<usize> {
+        let mut len = 0;
+
+        for chunk in chunks {
+            self.write_chunk(&chunk)?;
+            len += framed_len(&self.layout, chunk.1.len());
+        }
+
+        Ok(len)
+    }
+
+    /// Writes an entire container in one go: its header, the form type
+    /// and each of its children. Its size is computed up front, so the
+    /// writer doesn't need to be seekable, unlike with `begin_group`. A
+    /// container too large for its size to fit a `u32` is refused with
+    /// `ErrorKind::InvalidInput` before anything is written.
+    /// ```
+    /// use iffc::{Encoder, Decoder, Group, Chunk, FourCC, Endianness, Alignment};
+    ///
+    /// let aiff = Group {
+    ///     id: FourCC(*b"FORM"),
+    ///     form_type: FourCC(*b"AIFF"),
+    ///     children: vec![
+    ///         Chunk(FourCC(*b"COMM"), Box::new([0; 18])),
+    ///         Chunk(FourCC(*b"NAME"), Box::new(*b"odd")),
+    ///         Chunk(FourCC(*b"SSND"), Box::new([]))
+    ///     ]
+    /// };
+    ///
+    /// let mut enc = Encoder::new(Vec::new()).with_endianness(Endianness::Big);
+    /// enc.write_group(&aiff)?;
+    ///
+    /// let out = enc.into_inner();
+    /// assert_eq!(&out[4..8], &[0, 0, 0, 4 + 26 + 12 + 8]);
+    ///
+    /// let form = Decoder::new(&out[..]).with_endianness(Endianness::Big).next().unwrap().unwrap();
+    /// assert_eq!(Group::decode(&form, Endianness::Big, Alignment::Two).unwrap(), aiff);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_group(&mut self, group: &Group) -> io::Result<()> {
+        let size = group.children.iter()
+            .map(|c| framed_len(&self.layout, c.1.len()))
+            .try_fold(4usize, usize::checked_add)
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput,
+                "container body larger than 4 GiB"))?;
+
+        self.seen.push(Vec::new());
+        let children = group.children.iter().try_for_each(|c| self.claim(c.0));
+        self.seen.pop();
+
+        children?;
+        self.check_unique(group.id)?;
+
+        let w = writer(&mut self.w);
+        w.write_all(&layout::make_header(&self.layout, group.id, size))?;
+
+        let mut body = Hashing(&mut *w, Crc32::new());
+        body.1.update(&group.id.0);
+        body.write_all(&group.form_type.0)?;
+
+        for chunk in &group.children
+        { write_framed(&mut body, chunk.0, &chunk.1, &self.layout, self.pad)? }
+
+        let crc = body.1.finish();
+        write_tail(w, crc, size as usize, &self.layout, self.pad)?;
+
+        self.mark_written(group.id);
+        Ok(())
+    }
+
+    /// Writes a chunk followed by exactly the pad bytes it holds,
+    /// whatever the alignment and pad byte of the encoder. Checksums
+    /// are computed afresh. See `Decoder::raw`.
+    pub fn write_raw(&mut self, raw: &RawChunk) -> io::Result<()> {
+        self.check_unique(raw.chunk.0)?;
+        let w = writer(&mut self.w);
+        let chunk = &raw.chunk;
+        let size = chunk.checked_body()?.len() as u32;
+
+        let mut crc = Crc32::new();
+        crc.update(&chunk.0 .0);
+        crc.update(&chunk.1);
+
+        w.write_all(&layout::make_header(&self.layout, chunk.0, size))?;
+        w.write_all(&chunk.1)?;
+        w.write_all(&Endianness::Big.write_u32(crc.finish())[..layout::trailer_len(&self.layout)])?;
+        w.write_all(&raw.pad)?;
+
+        self.mark_written(chunk.0);
+        Ok(())
+    }
+
+    /// Writes `bytes` as they are, without any framing, such as the
+    /// vendor-specific filler some tools put between chunks. Nothing is
+    /// checked, so the output may well not decode. To write a chunk with
+    /// unusual padding use `write_raw` instead.
+    /// ```
+    /// use iffc::{Encoder, Chunk, FourCC};
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// enc.write_chunk(&Chunk(FourCC(*b"abc "), Box::new(*b"x")))?;
+    /// enc.write_bytes(b"\xAA\xBB")?;
+    /// enc.write_chunk(&Chunk(FourCC(*b"def "), Box::new([])))?;
+    ///
+    /// assert_eq!(enc.into_inner(), b"abc \x01\x00\x00\x00x\0\xAA\xBBdef \x00\x00\x00\x00");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()>
+    { writer(&mut self.w).write_all(bytes) }
+
+    /// Writes a chunk whose body of `size` bytes is copied from `src`
+    /// as it's written, rather than being held in memory. Fails with
+    /// `ErrorKind::UnexpectedEof` if `src` ends before `size` bytes,
+    /// in which case the writer holds a partial chunk.
+    /// ```
+    /// use std::io::{BufReader, Cursor};
+    /// use iffc::{Encoder, Decoder, FourCC};
+    ///
+    /// let body = (0..1 << 20).map(|i| i as u8).collect::<Vec<u8>>();
+    /// let mut src = BufReader::with_capacity(8 << 10, Cursor::new(&body));
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// enc.write_chunk_streaming(FourCC(*b"data"), 1 << 20, &mut src)?;
+    ///
+    /// let out = enc.into_inner();
+    /// let chunk = Decoder::new(&out[..]).next().unwrap().unwrap();
+    /// assert_eq!(&chunk.1[..], &body[..]);
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// let short = enc.write_chunk_streaming(FourCC(*b"data"), 4, &mut &b"abc"[..]);
+    /// assert_eq!(short.unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_chunk_streaming<R: Read>(&mut self, id: FourCC, size: u32, src: &mut R) -> io::Result<()> {
+        self.check_unique(id)?;
+        let w = writer(&mut self.w);
+        w.write_all(&layout::make_header(&self.layout, id, size))?;
+
+        let mut body = Hashing(&mut *w, Crc32::new());
+        body.1.update(&id.0);
+
+        if io::copy(&mut src.take(size as u64), &mut body)? != size as u64 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                "source ended before the declared chunk size"))
+        }
+
+        let crc = body.1.finish();
+        write_tail(w, crc, size as usize, &self.layout, self.pad)?;
+
+        self.mark_written(id);
+        Ok(())
+    }
+
+    /// Starts a chunk whose body of `size` bytes is written piece by
+    /// piece through the returned `ChunkBodyWriter`, its header being
+    /// written right away. Unlike `begin_group` the writer needn't be
+    /// seekable, as the size is known up front.
+    /// ```
+    /// use std::io::Write;
+    /// use iffc::{Encoder, Decoder, Chunk, FourCC};
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    ///
+    /// let mut data = enc.begin_chunk(FourCC(*b"data"), 7)?;
+    /// data.write_all(b"ab")?;
+    /// data.write_all(b"cde")?;
+    /// data.write_all(b"fg")?;
+    /// data.finish()?;
+    ///
+    /// let out = enc.into_inner();
+    /// assert_eq!(out.len(), 16);
+    /// assert_eq!(Decoder::new(&out[..]).next().unwrap().unwrap(),
+    ///     Chunk(FourCC(*b"data"), Box::new(*b"abcdefg")));
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// let mut short = enc.begin_chunk(FourCC(*b"data"), 4)?;
+    /// short.write_all(b"abc")?;
+    /// assert!(short.write_all(b"de").is_err());
+    /// assert!(short.finish().is_err());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn begin_chunk(&mut self, id: FourCC, size: u32) -> io::Result<ChunkBodyWriter<'_, W, L>> {
+        self.check_unique(id)?;
+        writer(&mut self.w).write_all(&layout::make_header(&self.layout, id, size))?;
+        self.mark_written(id);
+
+        let mut crc = Crc32::new();
+        crc.update(&id.0);
+
+        Ok(ChunkBodyWriter { enc: self, size, written: 0, crc })
+    }
+
+    /// Flushes the underlying writer. If writing a chunk through
+    /// `Extend::extend` failed, that error is returned instead.
+    ///
+    /// Dropping the encoder flushes it as well, but any error doing so
+    /// goes unnoticed, so call `flush` to observe it.
+    /// ```
+    /// use std::io::{BufWriter, Write};
+    /// use iffc::{Encoder, Chunk, FourCC};
+    ///
+    /// let mut out = Vec::new();
+    /// let mut enc = Encoder::new(BufWriter::new(&mut out));
+    ///
+    /// enc.write_chunk(&Chunk(FourCC(*b"data"), Box::new(*b"ab")))?;
+    /// assert!(enc.get_ref().get_ref().is_empty());
+    ///
+    /// enc.flush()?;
+    /// assert_eq!(enc.get_ref().get_ref().len(), 10);
+    ///
+    /// enc.write_chunk(&Chunk(FourCC(*b"note"), Box::new([])))?;
+    /// drop(enc);
+    /// assert_eq!(out.len(), 18);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => writer(&mut self.w).flush()
+        }
+    }
+
+    /// Reference to the underlying writer.
+    pub fn get_ref(&self) -> &W
+    { self.w.as_ref().expect("writer taken out of the encoder") }
+}
+
+/// The writer of an encoder, which is only ever taken out of it by the
+/// methods consuming the encoder.
+fn writer<W>(w: &mut Option<W>) -> &mut W
+{ w.as_mut().expect("writer taken out of the encoder") }
+
+/// Flushes the writer, ignoring errors, see `Encoder::flush`.
+impl<W: Write, L: ChunkLayout> Drop for Encoder<W, L> {
+    fn drop(&mut self) {
+        if let Some(w) = &mut self.w
+        { let _ = w.flush(); }
+    }
+}
+
+/// Writes every chunk of the iterator with `write_chunk`. Since `extend`
+/// can't fail, the first error is kept and returned by the next `flush`
+/// call, and the rest of the chunks are left unwritten.
+/// ```
+/// use std::io::{self, Read, Write, Seek, SeekFrom};
+/// use iffc::{Encoder, Chunk, FourCC};
+///
+/// let mut enc = Encoder::new(Vec::new());
+/// enc.extend(vec![
+///     Chunk(FourCC(*b"abc "), Box::new(*b"xyz")),
+///     Chunk(FourCC(*b"data"), Box::new(*b"de"))
+/// ]);
+///
+/// enc.flush().unwrap();
+/// assert_eq!(enc.into_inner().len(), 12 + 10);
+///
+/// // room for a single header.
+/// let mut enc = Encoder::new(io::Cursor::new([0u8; 8]));
+/// enc.extend(&[Chunk(FourCC(*b"data"), Box::new(*b"de"))]);
+///
+/// assert!(enc.flush().is_err());
+/// ```
+impl<W: Write, L: ChunkLayout> Extend<Chunk> for Encoder<W, L> {
+    fn extend<I: IntoIterator<Item = Chunk>>(&mut self, iter: I) {
+        for chunk in iter {
+            if self.error.is_some() { return }
+            self.extend(Some(&chunk));
+        }
+    }
+}
+
+impl<'a, W: Write, L: ChunkLayout> Extend<&'a Chunk> for Encoder<W, L> {
+    fn extend<I: IntoIterator<Item = &'a Chunk>>(&mut self, iter: I) {
+        if self.error.is_some() { return }
+
+        for chunk in iter {
+            if let Err(e) = self.write_chunk(chunk)
+            { self.error = Some(e); return }
+        }
+    }
+}
+
+impl<W: Write + Seek, L: ChunkLayout> Encoder<W, L> {
+    /// Starts a container chunk whose size isn't known in advance, its
+    /// header is written with a placeholder size which is patched once
+    /// `GroupWriter::finish` is called. The sub-chunks are written
+    /// through the returned `GroupWriter`, which derefs to the encoder,
+    /// and may themselves be containers started with `begin_group`.
+    /// Layouts with a checksum are refused with `InvalidInput`, as the
+    /// body of the container can't be read back to compute it.
+    /// ```
+    /// use std::io::Cursor;
+    /// use iffc::{Encoder, Decoder, Group, Chunk, FourCC, Endianness, Alignment};
+    ///
+    /// let mut enc = Encoder::new(Cursor::new(Vec::new()));
+    ///
+    /// let mut riff = enc.begin_group(FourCC(*b"RIFF"), FourCC(*b"WAVE"))?;
+    /// riff.write_chunk(&Chunk(FourCC(*b"fmt "), Box::new(*b"abc")))?;
+    /// riff.write_chunk(&Chunk(FourCC(*b"data"), Box::new(*b"de")))?;
+    /// riff.finish()?;
+    ///
+    /// let out = enc.into_inner().into_inner();
+    /// assert_eq!(&out[4..8], &[26, 0, 0, 0]);
+    ///
+    /// let riff = Decoder::new(&out[..]).next().unwrap().unwrap();
+    /// let wave = Group::decode(&riff, Endianness::Little, Alignment::Two).unwrap();
+    ///
+    /// assert_eq!(wave.form_type, FourCC(*b"WAVE"));
+    /// assert_eq!(wave.children, [
+    ///     Chunk(FourCC(*b"fmt "), Box::new(*b"abc")),
+    ///     Chunk(FourCC(*b"data"), Box::new(*b"de"))
+    /// ]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn begin_group(&mut self, id: FourCC, form_type: FourCC) -> io::Result<GroupWriter<'_, W, L>> {
+        let start = self.open_group(id, form_type)?;
+        Ok(GroupWriter { enc: self, start, open: true })
+    }
+
+    /// Turns the encoder into a `ContainerWriter` of a container with
+    /// `id` and `form_type`, framed as the encoder frames chunks.
+    pub fn into_container(mut self, id: FourCC, form_type: FourCC) -> io::Result<ContainerWriter<W, L>> {
+        let start = self.open_group(id, form_type)?;
+        Ok(ContainerWriter { enc: Some(self), start })
+    }
+
+    /// Writes the header of a container along with its form type,
+    /// returning the offset of the header, see `begin_group`.
+    fn open_group(&mut self, id: FourCC, form_type: FourCC) -> io::Result<u64> {
+        if layout::trailer_len(&self.layout) != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "containers can't be written with a checksummed layout"))
+        }
+
+        self.check_unique(id)?;
+
+        let w = writer(&mut self.w);
+        let start = w.stream_position()?;
+
+        w.write_all(&layout::make_header(&self.layout, id, 0))?;
+        w.write_all(&form_type.0)?;
+
+        self.mark_written(id);
+        self.seen.push(Vec::new());
+        Ok(start)
+    }
+
+    /// Patches the size of the container whose header is at `start` to
+    /// span everything written since, then pads it.
+    fn close_group(&mut self, start: u64) -> io::Result<()> {
+        self.seen.pop();
+
+        let w = writer(&mut self.w);
+        let end = w.stream_position()?;
+
+        let size = u32::try_from(end - start - 8).map_err(|_|
+            io::Error::new(io::ErrorKind::InvalidData, "container larger than 4 GiB"))?;
+
+        let size_at = match self.layout.field_order() {
+            FieldOrder::IdThenSize => 4,
+            FieldOrder::SizeThenId => 0
+        };
+
+        w.seek(SeekFrom::Start(start + size_at))?;
+        w.write_all(&self.layout.endianness().write_u32(size))?;
+        w.seek(SeekFrom::Start(end))?;
+
+        w.write_all(&[self.pad; 3][..self.layout.alignment().padding(size as usize)])
+    }
+}
+
+/// A container chunk being written, see `Encoder::begin_group`. If it
+/// is dropped without calling `finish` its size is left as zero, and
+/// the encoder goes back to writing at the level holding it.
+pub struct GroupWriter<'a, W: Write + Seek, L: ChunkLayout = Layout> {
+    enc: &'a mut Encoder<W, L>,
+    start: u64,
+    open: bool
+}
+
+impl<W: Write + Seek, L: ChunkLayout> GroupWriter<'_, W, L> {
+    /// Patches the size of the container to span its form type and all
+    /// of the sub-chunks written so far, then pads it.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.open = false;
+        self.enc.close_group(self.start)
+    }
+}
+
+/// Leaves the level of the container, see `GroupWriter`.
+impl<W: Write + Seek, L: ChunkLayout> Drop for GroupWriter<'_, W, L> {
+    fn drop(&mut self) {
+        if self.open
+        { self.enc.seen.pop(); }
+    }
+}
+
+impl<W: Write + Seek, L: ChunkLayout> Deref for GroupWriter<'_, W, L> {
+    type Target = Encoder<W, L>;
+    fn deref(&self) -> &Encoder<W, L> { self.enc }
+}
+
+impl<W: Write + Seek, L: ChunkLayout> DerefMut for GroupWriter<'_, W, L>
+{ fn deref_mut(&mut self) -> &mut Encoder<W, L> { self.enc } }
+
+/// A container chunk owning the writer it's written into, for output
+/// made of a single container such as a `RIFF` file. Its children are
+/// written with `push`, or through the `Encoder` it derefs to, and its
+/// size is patched by `finish`. Dropping it without calling `finish`
+/// finishes it all the same, ignoring errors.
+/// ```
+/// use std::io::Cursor;
+/// use iffc::{ContainerWriter, Decoder, Chunk, FourCC};
+///
+/// let mut riff = ContainerWriter::new(Cursor::new(Vec::new()), FourCC(*b"RIFF"), FourCC(*b"WAVE"))?;
+/// riff.push(&Chunk(FourCC(*b"fmt "), Box::new([0; 16])))?;
+/// riff.push(&Chunk(FourCC(*b"data"), Box::new(*b"odd")))?;
+///
+/// let out = riff.finish()?.into_inner();
+/// assert_eq!(&out[4..8], &(4u32 + 24 + 12).to_le_bytes());
+///
+/// let mut dec = Decoder::new(&out[..]);
+/// assert_eq!(dec.read_form_type()?, FourCC(*b"WAVE"));
+/// assert_eq!(dec.map(|c| c.unwrap().0).collect::<Vec<_>>(), [FourCC(*b"fmt "), FourCC(*b"data")]);
+///
+/// // dropped unfinished, into a buffer of its own.
+/// let mut out = Vec::new();
+/// let mut list = ContainerWriter::new(Cursor::new(&mut out), FourCC(*b"LIST"), FourCC(*b"INFO"))?;
+/// list.push(&Chunk(FourCC(*b"INAM"), Box::new(*b"x")))?;
+/// drop(list);
+/// assert_eq!(out, b"LIST\x0E\x00\x00\x00INFOINAM\x01\x00\x00\x00x\0");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct ContainerWriter<W: Write + Seek, L: ChunkLayout = Layout> {
+    enc: Option<Encoder<W, L>>,
+    start: u64
+}
+
+impl<W: Write + Seek> ContainerWriter<W> {
+    /// Starts a RIFF container with `id` and `form_type` at the current
+    /// position of `w`, see `Encoder::into_container` for other layouts.
+    pub fn new(w: W, id: FourCC, form_type: FourCC) -> io::Result<Self>
+    { Encoder::new(w).into_container(id, form_type) }
+}
+
+impl<W: Write + Seek, L: ChunkLayout> ContainerWriter<W, L> {
+    /// Writes `chunk` into the container.
+    pub fn push(&mut self, chunk: &Chunk) -> io::Result<()>
+    { self.write_chunk(chunk) }
+
+    /// Patches the size of the container and pads it, then unwraps the
+    /// underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut enc = self.enc.take().expect("container already finished");
+        enc.close_group(self.start)?;
+        enc.finish()
+    }
+}
+
+impl<W: Write + Seek, L: ChunkLayout> Deref for ContainerWriter<W, L> {
+    type Target = Encoder<W, L>;
+    fn deref(&self) -> &Encoder<W, L> { self.enc.as_ref().expect("container already finished") }
+}
+
+impl<W: Write + Seek, L: ChunkLayout> DerefMut for ContainerWriter<W, L> {
+    fn deref_mut(&mut self) -> &mut Encoder<W, L>
+    { self.enc.as_mut().expect("container already finished") }
+}
+
+/// Finishes the container, ignoring errors, see `ContainerWriter::finish`.
+impl<W: Write + Seek, L: ChunkLayout> Drop for ContainerWriter<W, L> {
+    fn drop(&mut self) {
+        if let Some(enc) = &mut self.enc
+        { let _ = enc.close_group(self.start); }
+    }
+}
+
+/// The body of a chunk being written, see `Encoder::begin_chunk`. Bytes
+/// past the declared size are refused with `ErrorKind::InvalidInput`.
+/// If it's dropped without calling `finish` the chunk is left without
+/// its checksum and padding.
+///
+/// Being a `Write`, it can be handed to code which knows nothing about
+/// chunks, such as another format's encoder. Flushing it only flushes
+/// the underlying writer, the chunk is still ended by `finish` alone.
+/// ```
+/// use std::io::{self, Write};
+/// use iffc::{Encoder, Decoder, Chunk, FourCC};
+///
+/// fn encode_image(w: &mut dyn Write) -> io::Result<()> {
+///     w.write_all(b"P5 1 1 ")?;
+///     w.flush()?;
+///     w.write_all(b"255 x")
+/// }
+///
+/// let mut enc = Encoder::new(Vec::new());
+/// let mut body = enc.begin_chunk(FourCC(*b"PGM "), 12)?;
+/// encode_image(&mut body)?;
+/// assert_eq!(body.remaining(), 0);
+/// body.finish()?;
+///
+/// let out = enc.into_inner();
+/// assert_eq!(Decoder::new(&out[..]).collect::<Result<Vec<Chunk>, _>>().unwrap(),
+///     [Chunk(FourCC(*b"PGM "), Box::new(*b"P5 1 1 255 x"))]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct ChunkBodyWriter<'a, W: Write, L: ChunkLayout = Layout> {
+    enc: &'a mut Encoder<W, L>,
+    size: u32,
+    written: u32,
+    crc: Crc32
+}
+
+impl<W: Write, L: ChunkLayout> ChunkBodyWriter<'_, W, L> {
+    /// Number of body bytes which are yet to be written.
+    pub fn remaining(&self) -> u32 { self.size - self.written }
+
+    /// Ends the chunk with its checksum and padding, failing with
+    /// `ErrorKind::InvalidInput` if fewer bytes than declared were
+    /// written, in which case the writer holds a partial chunk.
+    pub fn finish(self) -> io::Result<()> {
+        if self.written != self.size {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "chunk body shorter than its declared size"))
+        }
+
+        let enc = self.enc;
+        write_tail(writer(&mut enc.w), self.crc.finish(), self.size as usize, &enc.layout, enc.pad)
+    }
+}
+
+impl<W: Write, L: ChunkLayout> Write for ChunkBodyWriter<'_, W, L> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.remaining() as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                "chunk body longer than its declared size"))
+        }
+
+        let n = writer(&mut self.enc.w).write(buf)?;
+        self.crc.update(&buf[..n]);
+        self.written += n as u32;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> { writer(&mut self.enc.w).flush() }
+}
+
+/// Writes a chunk made of `id` and `body` into `w`, framed as described
+/// by `layout` and padded with `pad` bytes. A body whose size doesn't
+/// fit a `u32` fails with `ErrorKind::InvalidInput` before anything is
+/// written.
+pub(crate) fn write_framed<W: Write + ?Sized, L: ChunkLayout>(
+    w: &mut W, id: FourCC, body: &[u8], layout: &L, pad: u8
+) -> io::Result<()> {
+    let size = u32::try_from(body.len()).map_err(|_|
+        io::Error::new(io::ErrorKind::InvalidInput, "chunk body larger than 4 GiB"))?;
+
+    let mut crc = Crc32::new();
+
+    if layout::trailer_len(layout) != 0 {
+        crc.update(&id.0);
+        crc.update(body);
+    }
+
+    w.write_all(&layout::make_header(layout, id, size))?;
+    w.write_all(body)?;
+    write_tail(w, crc.finish(), body.len(), layout, pad)
+}
+
+/// Number of bytes a chunk with a body of `len` bytes takes up once
+/// framed as described by `layout`.
+fn framed_len<L: ChunkLayout>(layout: &L, len: usize) -> usize
+{ 8 + len + layout::trailer_len(layout) + layout.alignment().padding(len) }
+
+/// Writes what follows a body of `len` bytes, its checksum `crc` if
+/// the layout has one and the `pad` bytes.
+pub(crate) fn write_tail<W: Write + ?Sized, L: ChunkLayout>(w: &mut W, crc: u32, len: usize, layout: &L, pad: u8) -> io::Result<()> {
+    let crc = Endianness::Big.write_u32(crc);
+
+    w.write_all(&crc[..layout::trailer_len(layout)])?;
+    w.write_all(&[pad; 3][..layout.alignment().padding(len)])
+}
+
+/// Writer which computes the CRC-32 of what passes through it.
+struct Hashing<'a, W: Write>(&'a mut W, Crc32);
+
+impl<W: Write> Write for Hashing<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.0.write(buf)?;
+        self.1.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> { self.0.flush() }
+}
+
+impl<W: Write, L: ChunkLayout> Shl<Chunk> for Encoder<W, L> {
+    type Output = Option<Self>;
+    
+    /// Writes the chunk with `write_chunk`, `None` is returned if it
+    /// fails, in which case the writer may hold a partial chunk and
+    /// must not be used further.
+    fn shl(mut self, chunk: Chunk) -> Option<Self> {
+        self.write_chunk(&chunk).ok()?;
+        Some(self)
+    }
+}
+
+/// Chunks encoded as RIFF one after another, byte-identical to writing
+/// each of them with an `Encoder` with the default settings. Collected
+/// from chunks as an `io::Result`, a body larger than `u32::MAX` bytes
+/// failing with `ErrorKind::InvalidInput`.
+/// ```
+/// use iffc::{Chunk, Encoded, FourCC};
+///
+/// let chunks = vec![
+///     Chunk(FourCC(*b"abc "), Box::new(*b"xyz")),
+///     Chunk(FourCC(*b"JUNK"), Box::new([0; 4])),
+///     Chunk(FourCC(*b"data"), Box::new(*b"de"))
+/// ];
+///
+/// let bytes: Encoded = chunks.into_iter()
+///     .filter(|c| c.id() != "JUNK")
+///     .collect::<std::io::Result<_>>()?;
+///
+/// assert_eq!(bytes.0, b"abc \x03\x00\x00\x00xyz\0data\x02\x00\x00\x00de");
+///
+/// // collecting chunks into a `Vec` is left unambiguous.
+/// let again = iffc::Decoder::new(&bytes.0[..]).map(Result::unwrap).collect::<Vec<_>>();
+/// assert_eq!(again.len(), 2);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Encoded(pub Vec<u8>);
+
+impl From<Encoded> for Vec<u8>
+{ fn from(e: Encoded) -> Self { e.0 } }
+
+impl AsRef<[u8]> for Encoded
+{ fn as_ref(&self) -> &[u8] { &self.0 } }
+
+impl FromIterator<Chunk> for io::Result<Encoded> {
+    fn from_iter<I: IntoIterator<Item = Chunk>>(iter: I) -> Self {
+        let mut enc = Encoder::new(Vec::new());
+
+        for chunk in iter
+        { enc.write_chunk(&chunk)? }
+
+        Ok(Encoded(enc.into_inner()))
+    }
+}
@@ -0,0 +1,147 @@
+use core::fmt;
+use crate::FourCC;
+
+/// Reasons for which a `Decoder` could not construct a chunk.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The underlying reader failed.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// The stream ended inside of a chunk header or the checksum
+    /// following a body, after `got` of the `expected` bytes were read.
+    UnexpectedEof { expected: usize, got: usize },
+    /// The stream ended before the body of the chunk with `id`, whose
+    /// header is at `offset`, was complete: only `got` of the
+    /// `expected` bytes declared by the header were available.
+    TruncatedBody { id: FourCC, offset: u64, expected: u32, got: usize },
+    /// The header declared a body of `size` bytes, more than the
+    /// `max` which the decoder was configured to accept.
+    ChunkTooLarge { size: u32, max: u32 },
+    /// Containers were nested deeper than the `max` levels allowed.
+    DepthExceeded { max: usize },
+    /// A strict decoder read an `id` which isn't printable ASCII from
+    /// the header at `offset`, which usually means it lost sync.
+    InvalidId { id: FourCC, offset: u64 },
+    /// The checksum `found` after a body differs from the `expected`
+    /// one computed over its id and body.
+    BadChecksum { expected: u32, found: u32 },
+    /// The stream held more than the `max` chunks allowed.
+    TooManyChunks { max: u32 },
+    /// Reading the next body would have taken the bodies read in total
+    /// over the `budget` of bytes allowed.
+    BudgetExceeded { budget: u64 },
+    /// A buffer meant to hold a single chunk had `count` bytes left
+    /// over after its body and padding.
+    TrailingBytes { count: usize },
+    /// The chunk with `id` at `offset` was expected to be a container,
+    /// such as a `RIFF` or `FORM`.
+    NotContainer { id: FourCC, offset: u64 },
+    /// The body of `size` bytes declared by the chunk with `id`, along
+    /// with its framing, is larger than this platform can address.
+    SizeOverflow { id: FourCC, size: u32 },
+    /// The chunk with id `after` at `offset` came before any chunk with
+    /// id `before`, see `Decoder::ordered`.
+    OutOfOrder { before: FourCC, after: FourCC, offset: u64 }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(e) => write!(f, "i/o error: {}", e),
+            Self::UnexpectedEof { expected, got } =>
+                write!(f, "unexpected end of stream in chunk header ({} of {} bytes)", got, expected),
+            Self::TruncatedBody { id, offset, expected, got } =>
+                write!(f, "truncated body of chunk {:?} at offset {} ({} of {} bytes)", id, offset, got, expected),
+            Self::ChunkTooLarge { size, max } =>
+                write!(f, "chunk body of {} bytes exceeds the limit of {} bytes", size, max),
+            Self::DepthExceeded { max } =>
+                write!(f, "containers nested deeper than {} levels", max),
+            Self::InvalidId { id, offset } =>
+                write!(f, "non-printable chunk id {:?} at offset {}", id, offset),
+            Self::BadChecksum { expected, found } =>
+                write!(f, "chunk checksum {:08X} doesn't match the computed {:08X}", found, expected),
+            Self::TooManyChunks { max } =>
+                write!(f, "stream holds more than {} chunks", max),
+            Self::BudgetExceeded { budget } =>
+                write!(f, "chunk bodies exceed the memory budget of {} bytes", budget),
+            Self::TrailingBytes { count } =>
+                write!(f, "{} bytes left over after the chunk", count),
+            Self::NotContainer { id, offset } =>
+                write!(f, "chunk {:?} at offset {} isn't a container", id, offset),
+            Self::SizeOverflow { id, size } =>
+                write!(f, "chunk {:?} of {} bytes too large for this platform", id, size),
+            Self::OutOfOrder { before, after, offset } =>
+                write!(f, "chunk {:?} at offset {} comes before any {:?}", after, offset, before)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for DecodeError
+{ fn from(e: std::io::Error) -> Self { Self::Io(e) } }
+
+/// Reasons for which a `Chunk` could not be constructed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ChunkError {
+    /// The body is `len` bytes long, more than the `u32::MAX` bytes
+    /// which a chunk header can declare.
+    TooLarge { len: usize },
+    /// A body can't be split into pieces of no bytes.
+    ZeroSplit
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TooLarge { len } =>
+                write!(f, "chunk body of {} bytes exceeds the 4 GiB limit", len),
+            Self::ZeroSplit =>
+                f.write_str("chunk split into pieces of zero bytes")
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChunkError {}
+
+/// An `ErrorKind::InvalidInput`, as the chunk can't be written.
+#[cfg(feature = "std")]
+impl From<ChunkError> for std::io::Error
+{ fn from(e: ChunkError) -> Self { Self::new(std::io::ErrorKind::InvalidInput, e) } }
+
+/// Reasons for which the body of a chunk couldn't be parsed into one
+/// of the typed structures, such as `wav::Fmt`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FormatError {
+    /// The body is `got` bytes long, while at least `expected` bytes
+    /// are needed.
+    TooShort { expected: usize, got: usize },
+    /// Samples encoded as `format_tag` with `bits_per_sample` bits can't
+    /// be decoded.
+    Unsupported { format_tag: u16, bits_per_sample: u16 }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TooShort { expected, got } =>
+                write!(f, "chunk body too short ({} of {} bytes)", got, expected),
+            Self::Unsupported { format_tag, bits_per_sample } =>
+                write!(f, "unsupported {}-bit samples of format {:#06X}", bits_per_sample, format_tag)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FormatError {}
@@ -0,0 +1,160 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::str::FromStr;
+use core::fmt;
+
+/// Four-byte code which identifies the meaning of a chunk, eg. `RIFF`,
+/// `fmt ` or `data`. IFF expects these to be printable ASCII, but any
+/// four bytes are accepted to be able to read malformed files.
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use iffc::FourCC;
+///
+/// let id = FourCC::try_from("fmt ").unwrap();
+///
+/// assert!(id == "fmt ");
+/// assert_eq!(id, FourCC::from(*b"fmt "));
+/// assert_eq!(FourCC(*b"\x00abc").to_string(), "\\x00abc");
+/// assert!(FourCC::try_from("fmt").is_err());
+/// ```
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct FourCC(pub [u8; 4]);
+
+/// Error returned when converting a string which isn't exactly four
+/// bytes long into a `FourCC`, carries the length of the string.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct InvalidFourCC(pub usize);
+
+impl fmt::Display for InvalidFourCC {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    { write!(f, "chunk id must be 4 bytes long, not {}", self.0) }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidFourCC {}
+
+impl FourCC {
+    /// Whether all four bytes are printable ASCII (`0x20..=0x7E`), as
+    /// IFF expects of every id.
+    /// ```
+    /// use iffc::FourCC;
+    ///
+    /// assert!(FourCC(*b"fmt ").is_printable());
+    /// assert!(!FourCC(*b"\x00abc").is_printable());
+    /// ```
+    pub fn is_printable(&self) -> bool
+    { self.0.iter().all(|b| (0x20..=0x7E).contains(b)) }
+
+    /// Writes the code as ASCII, escaping the non-printable characters
+    /// and backslashes as `\xNN` and `\\`. Characters in `quote` are
+    /// escaped with a backslash as well.
+    pub(crate) fn write_escaped(&self, f: &mut fmt::Formatter, quote: &[u8]) -> fmt::Result {
+        for &b in &self.0 {
+            match b {
+                b'\\' => f.write_str("\\\\")?,
+                _ if quote.contains(&b) => write!(f, "\\{}", b as char)?,
+                0x20..=0x7E => write!(f, "{}", b as char)?,
+                _ => write!(f, "\\x{:02X}", b)?
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<[u8; 4]> for FourCC
+{ fn from(b: [u8; 4]) -> Self { Self(b) } }
+
+impl From<FourCC> for [u8; 4]
+{ fn from(id: FourCC) -> Self { id.0 } }
+
+impl TryFrom<&str> for FourCC {
+    type Error = InvalidFourCC;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        <[u8; 4]>::try_from(s.as_bytes())
+            .map(Self)
+            .map_err(|_| InvalidFourCC(s.len()))
+    }
+}
+
+/// Parses the textual form of a code as written by `Display`, where
+/// `\xNN` and `\\` stand for a single byte each. The result must be
+/// exactly four bytes long, other backslashes are taken literally.
+/// ```
+/// use iffc::FourCC;
+///
+/// let id = FourCC(*b"\x00ab\\");
+/// assert_eq!(id.to_string().parse::<FourCC>(), Ok(id));
+/// assert_eq!("fmt ".parse::<FourCC>(), Ok(FourCC(*b"fmt ")));
+/// assert!("\x00abcd".parse::<FourCC>().is_err());
+///
+/// // an escape is two hex digits, no sign.
+/// assert_eq!("\\x+F".parse::<FourCC>(), Ok(FourCC(*b"\\x+F")));
+/// assert!("\\x+Fabc".parse::<FourCC>().is_err());
+/// ```
+impl FromStr for FourCC {
+    type Err = InvalidFourCC;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.as_bytes();
+        let mut bytes = Vec::with_capacity(4);
+        let mut i = 0;
+
+        while i < s.len() {
+            let hex = s.get(i + 2..i + 4)
+                .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|h| core::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+
+            match (s[i], s.get(i + 1), hex) {
+                (b'\\', Some(b'x'), Some(b)) => { bytes.push(b); i += 4 },
+                (b'\\', Some(&c), _) if c == b'\\' || c == b'"' => { bytes.push(c); i += 2 },
+                (c, _, _) => { bytes.push(c); i += 1 }
+            }
+        }
+
+        <[u8; 4]>::try_from(&bytes[..])
+            .map(Self)
+            .map_err(|_| InvalidFourCC(bytes.len()))
+    }
+}
+
+/// Codes compare equal to the bytes of literals holding exactly the
+/// same four bytes, anything shorter or longer never matches.
+/// ```
+/// use iffc::FourCC;
+///
+/// let id = FourCC(*b"fmt ");
+///
+/// assert!(id == *b"fmt " && id != *b"data");
+/// assert!(id == b"fmt " && id != b"data");
+/// assert!(id == &b"fmt "[..] && id != &b"fmt"[..]);
+/// assert!(id == "fmt " && id != "data");
+/// assert!(id != "fmt" && id != "fmt  ");
+/// ```
+impl PartialEq<[u8; 4]> for FourCC
+{ fn eq(&self, b: &[u8; 4]) -> bool { &self.0 == b } }
+
+impl PartialEq<&[u8; 4]> for FourCC
+{ fn eq(&self, b: &&[u8; 4]) -> bool { &self.0 == *b } }
+
+impl PartialEq<&[u8]> for FourCC
+{ fn eq(&self, b: &&[u8]) -> bool { &self.0[..] == *b } }
+
+impl PartialEq<&str> for FourCC
+{ fn eq(&self, s: &&str) -> bool { &self.0[..] == s.as_bytes() } }
+
+impl fmt::Display for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    { self.write_escaped(f, &[]) }
+}
+
+impl fmt::Debug for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"")?;
+        self.write_escaped(f, b"\"")?;
+        f.write_str("\"")
+    }
+}
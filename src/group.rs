@@ -0,0 +1,65 @@
+use alloc::vec::Vec;
+use crate::{Chunk, FourCC, Endianness, Alignment, DecodeError, SliceDecoder, Layout};
+
+/// A container chunk (`RIFF`, `LIST`, `FORM` or `CAT `) split into its
+/// parts: its body starts with a four-byte form type, such as `WAVE`
+/// or `AIFF`, which is followed by the nested sub-chunks.
+///
+/// ```
+/// use iffc::{Decoder, Group, Chunk, FourCC, Endianness, Alignment};
+///
+/// let inp = b"RIFF\x1A\x00\x00\x00WAVE\
+///             fmt \x03\x00\x00\x00abc\0\
+///             data\x02\x00\x00\x00de";
+///
+/// let riff = Decoder::new(&inp[..]).next().unwrap().unwrap();
+/// let wave = Group::decode(&riff, Endianness::Little, Alignment::Two).unwrap();
+///
+/// assert_eq!(wave, Group {
+///     id: FourCC(*b"RIFF"),
+///     form_type: FourCC(*b"WAVE"),
+///     children: vec![
+///         Chunk(FourCC(*b"fmt "), Box::new(*b"abc")),
+///         Chunk(FourCC(*b"data"), Box::new(*b"de"))
+///     ]
+/// });
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Group {
+    pub id: FourCC,
+    pub form_type: FourCC,
+    pub children: Vec<Chunk>
+}
+
+impl Group {
+    /// Whether `id` is one of the well-known container ids, `RIFF` and
+    /// `LIST` of Microsoft RIFF or `FORM`, `LIST` and `CAT ` of EA IFF.
+    /// ```
+    /// use iffc::{Group, FourCC};
+    ///
+    /// assert!(Group::is_container(FourCC(*b"LIST")));
+    /// assert!(!Group::is_container(FourCC(*b"data")));
+    /// ```
+    pub fn is_container(id: FourCC) -> bool
+    { matches!(&id.0, b"RIFF" | b"LIST" | b"FORM" | b"CAT ") }
+
+    /// Splits the body of a container chunk into its form type and
+    /// sub-chunks, whose size fields and padding are read according
+    /// to `endianness` and `alignment`. The sub-chunks must end with
+    /// the body, one running past it is reported as truncated. A body
+    /// too short to hold the form type is a `DecodeError::UnexpectedEof`.
+    pub fn decode(chunk: &Chunk, endianness: Endianness, alignment: Alignment) -> Result<Self, DecodeError> {
+        if chunk.1.len() < 4
+        { return Err(DecodeError::UnexpectedEof { expected: 4, got: chunk.1.len() }) }
+
+        let mut form_type = [0u8; 4];
+        form_type.copy_from_slice(&chunk.1[..4]);
+
+        let children = SliceDecoder::new(&chunk.1[4..])
+            .with_layout(Layout { endianness, alignment, ..Layout::RIFF })
+            .map(|c| c.map(Chunk::from))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { id: chunk.0, form_type: FourCC(form_type), children })
+    }
+}
@@ -0,0 +1,295 @@
+use std::io::{Read, Seek, SeekFrom};
+use crate::{Chunk, FourCC, Decoder, DecodeError, Group};
+
+/// A container found by a `GroupingDecoder`, its sub-chunks are only
+/// read once asked for with `children`. `offset` is where the first of
+/// them begins and `size` how many bytes they span, form type excluded.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct GroupHandle {
+    pub id: FourCC,
+    pub form_type: FourCC,
+    pub offset: u64,
+    pub size: u32
+}
+
+/// What a `GroupingDecoder` yields, either an ordinary chunk or a
+/// container whose children haven't been read yet.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Entry {
+    Leaf(Chunk),
+    Group(GroupHandle)
+}
+
+/// Descends into the containers of a seekable stream lazily, unlike the
+/// `TreeDecoder`. Containers (the ids known to `Group::is_container`)
+/// come out as a `GroupHandle` and their body is seeked past, so that
+/// only the chunks actually iterated over are held in memory. The
+/// children of a container are iterated with `children`, which stops
+/// exactly at its end.
+///
+/// ```
+/// use std::io::Cursor;
+/// use iffc::{GroupingDecoder, Entry, Encoder, Chunk, FourCC};
+///
+/// let mut enc = Encoder::new(Cursor::new(Vec::new()));
+/// let mut riff = enc.begin_group(FourCC(*b"RIFF"), FourCC(*b"WAVE"))?;
+/// riff.write_chunk(&Chunk(FourCC(*b"fmt "), Box::new(*b"abc")))?;
+///
+/// let mut list = riff.begin_group(FourCC(*b"LIST"), FourCC(*b"INFO"))?;
+/// list.write_chunk(&Chunk(FourCC(*b"INAM"), Box::new(*b"x\0")))?;
+/// list.finish()?;
+///
+/// riff.write_chunk(&Chunk(FourCC(*b"data"), vec![7; 1 << 16].into()))?;
+/// riff.finish()?;
+/// enc.write_chunk(&Chunk(FourCC(*b"next"), Box::new([])))?;
+///
+/// let mut dec = GroupingDecoder::new(Cursor::new(enc.into_inner().into_inner()));
+///
+/// let riff = match dec.next().unwrap()? {
+///     Entry::Group(riff) => riff,
+///     e => panic!("unexpected {:?}", e)
+/// };
+/// assert_eq!(riff.form_type, FourCC(*b"WAVE"));
+///
+/// let mut children = dec.children(&riff);
+/// assert!(matches!(children.next().unwrap()?, Entry::Leaf(c) if c.id() == "fmt "));
+///
+/// let list = match children.next().unwrap()? {
+///     Entry::Group(list) => list,
+///     e => panic!("unexpected {:?}", e)
+/// };
+///
+/// let names = children.children(&list).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(names, [Entry::Leaf(Chunk(FourCC(*b"INAM"), Box::new(*b"x\0")))]);
+///
+/// assert!(matches!(children.next().unwrap()?, Entry::Leaf(c) if c.1.len() == 1 << 16));
+/// assert!(children.next().is_none());
+///
+/// // the sibling after the container.
+/// assert!(matches!(dec.next().unwrap()?, Entry::Leaf(c) if c.id() == "next"));
+/// assert!(dec.next().is_none());
+/// # Ok::<(), iffc::DecodeError>(())
+/// ```
+///
+/// Errors tell offsets from the start of the stream, containers
+/// seeked past included:
+/// ```
+/// use std::io::Cursor;
+/// use iffc::{GroupingDecoder, DecodeError};
+///
+/// let inp = Cursor::new(b"LIST\x08\x00\x00\x00INFOabcddata\x04\x00\x00\x00ab");
+/// let mut dec = GroupingDecoder::new(inp);
+///
+/// assert!(dec.next().unwrap().is_ok());
+/// assert!(matches!(dec.next(), Some(Err(DecodeError::TruncatedBody { offset: 16, .. }))));
+/// ```
+pub struct GroupingDecoder<R: Read + Seek> {
+    dec: Decoder<R>,
+    next: Option<u64>,
+    done: bool
+}
+
+impl<R: Read + Seek> GroupingDecoder<R> {
+    /// Reads little-endian (RIFF) chunks from `r`.
+    pub fn new(r: R) -> Self
+    { Self { dec: Decoder::new(r), next: None, done: false } }
+
+    /// Iterates over the children of `group`, which must have been
+    /// yielded by this decoder or one of its `Children`.
+    pub fn children(&mut self, group: &GroupHandle) -> Children<'_, R> {
+        Children {
+            pos: group.offset,
+            end: group.offset.saturating_add(group.size as u64),
+            dec: self,
+            done: false
+        }
+    }
+
+    /// Iterates over every chunk which isn't a container, however deep,
+    /// along with the form types of the containers holding it from the
+    /// outermost one inwards.
+    /// ```
+    /// use std::io::Cursor;
+    /// use iffc::{GroupingDecoder, Encoder, Chunk, FourCC};
+    ///
+    /// let mut enc = Encoder::new(Cursor::new(Vec::new()));
+    /// let mut riff = enc.begin_group(FourCC(*b"RIFF"), FourCC(*b"WAVE"))?;
+    ///
+    /// let mut list = riff.begin_group(FourCC(*b"LIST"), FourCC(*b"INFO"))?;
+    /// list.write_chunk(&Chunk(FourCC(*b"INAM"), Box::new(*b"x")))?;
+    /// list.finish()?;
+    ///
+    /// riff.write_chunk(&Chunk(FourCC(*b"data"), Box::new([1, 2])))?;
+    /// riff.finish()?;
+    /// enc.write_chunk(&Chunk(FourCC(*b"next"), Box::new([])))?;
+    ///
+    /// let dec = GroupingDecoder::new(Cursor::new(enc.into_inner().into_inner()));
+    /// let paths = dec.paths()
+    ///     .map(|c| c.map(|(path, chunk)| (path, chunk.0)))
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// assert_eq!(paths, [
+    ///     (vec![FourCC(*b"WAVE"), FourCC(*b"INFO")], FourCC(*b"INAM")),
+    ///     (vec![FourCC(*b"WAVE")], FourCC(*b"data")),
+    ///     (vec![], FourCC(*b"next"))
+    /// ]);
+    /// # Ok::<(), iffc::DecodeError>(())
+    /// ```
+    pub fn paths(self) -> Paths<R>
+    { Paths { dec: self, stack: Vec::new() } }
+
+    /// Unwraps the underlying reader.
+    pub fn into_inner(self) -> R { self.dec.into_inner() }
+
+    /// Reads the entry whose header is at `pos`, along with where the
+    /// one after it begins. `None` once `end` or the end of the stream
+    /// is reached.
+    fn entry(&mut self, pos: u64, end: Option<u64>) -> Result<Option<(Entry, u64)>, DecodeError> {
+        if end.is_some_and(|end| pos >= end) { return Ok(None) }
+
+        self.dec.get_mut().seek(SeekFrom::Start(pos))?;
+        self.dec.set_offset(pos);
+
+        let (id, size) = match self.dec.read_header()? {
+            Some(h) => h,
+            None => return Ok(None)
+        };
+
+        let overflow = || DecodeError::SizeOverflow { id, size };
+
+        let body = pos.checked_add(8).ok_or_else(overflow)?;
+        let next = body.checked_add(size as u64 + self.dec.tail_len(size as usize) as u64)
+            .ok_or_else(overflow)?;
+
+        if let Some(end) = end {
+            if body > end
+            { return Err(DecodeError::UnexpectedEof { expected: 8, got: (end - pos) as usize }) }
+
+            if size as u64 > end - body
+            { return Err(DecodeError::TruncatedBody { id, offset: pos, expected: size, got: (end - body) as usize }) }
+        }
+
+        if !Group::is_container(id)
+        { return Ok(Some((Entry::Leaf(self.dec.read_body(id, size)?), next))) }
+
+        if size < 4
+        { return Err(DecodeError::UnexpectedEof { expected: 4, got: size as usize }) }
+
+        let mut form_type = [0u8; 4];
+        self.dec.get_mut().read_exact(&mut form_type)?;
+
+        Ok(Some((Entry::Group(GroupHandle {
+            id,
+            form_type: FourCC(form_type),
+            offset: body + 4,
+            size: size - 4
+        }), next)))
+    }
+}
+
+impl<R: Read + Seek> Iterator for GroupingDecoder<R> {
+    type Item = Result<Entry, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done { return None }
+
+        let pos = match self.next {
+            Some(pos) => pos,
+            None => match self.dec.get_mut().stream_position() {
+                Ok(pos) => pos,
+                Err(e) => { self.done = true; return Some(Err(e.into())) }
+            }
+        };
+
+        match self.entry(pos, None) {
+            Ok(Some((entry, next))) => { self.next = Some(next); Some(Ok(entry)) },
+            Ok(None) => { self.done = true; None },
+            Err(e) => { self.done = true; Some(Err(e)) }
+        }
+    }
+}
+
+/// Iterator over the children of a container, see
+/// `GroupingDecoder::children`. An error ends the iteration.
+pub struct Children<'a, R: Read + Seek> {
+    dec: &'a mut GroupingDecoder<R>,
+    pos: u64,
+    end: u64,
+    done: bool
+}
+
+impl<R: Read + Seek> Children<'_, R> {
+    /// Iterates over the children of `group`, a container among these
+    /// children.
+    pub fn children(&mut self, group: &GroupHandle) -> Children<'_, R>
+    { self.dec.children(group) }
+}
+
+impl<R: Read + Seek> Iterator for Children<'_, R> {
+    type Item = Result<Entry, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done { return None }
+
+        match self.dec.entry(self.pos, Some(self.end)) {
+            Ok(Some((entry, next))) => { self.pos = next; Some(Ok(entry)) },
+            Ok(None) => { self.done = true; None },
+            Err(e) => { self.done = true; Some(Err(e)) }
+        }
+    }
+}
+
+/// Iterator over the chunks of a `GroupingDecoder` paired with the form
+/// types of their containers, see `GroupingDecoder::paths`. An error
+/// ends the iteration.
+pub struct Paths<R: Read + Seek> {
+    dec: GroupingDecoder<R>,
+    stack: Vec<Frame>
+}
+
+/// A container being descended into by `Paths`.
+struct Frame {
+    form_type: FourCC,
+    end: u64,
+    next: u64
+}
+
+impl<R: Read + Seek> Paths<R> {
+    fn read_chunk(&mut self) -> Result<Option<(Vec<FourCC>, Chunk)>, DecodeError> {
+        let mut pos = match self.dec.next {
+            Some(pos) => pos,
+            None => self.dec.dec.get_mut().stream_position()?
+        };
+
+        loop {
+            let end = self.stack.last().map(|f| f.end);
+
+            match self.dec.entry(pos, end)? {
+                Some((Entry::Leaf(chunk), next)) => {
+                    self.dec.next = Some(next);
+                    return Ok(Some((self.stack.iter().map(|f| f.form_type).collect(), chunk)))
+                },
+                Some((Entry::Group(g), next)) => {
+                    self.stack.push(Frame { form_type: g.form_type, end: g.offset + g.size as u64, next });
+                    pos = g.offset;
+                },
+                None => match self.stack.pop() {
+                    Some(frame) => pos = frame.next,
+                    None => return Ok(None)
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for Paths<R> {
+    type Item = Result<(Vec<FourCC>, Chunk), DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.dec.done { return None }
+
+        let chunk = self.read_chunk().transpose();
+        self.dec.done = !matches!(chunk, Some(Ok(_)));
+        chunk
+    }
+}
@@ -0,0 +1,61 @@
+//! Ids of the chunks and form types found in common RIFF, AIFF and IFF
+//! files, spelled out once so that trailing spaces aren't missed.
+//! ```
+//! use iffc::{ids, Chunk, FourCC};
+//!
+//! assert_eq!(ids::FMT, FourCC(*b"fmt "));
+//! assert_eq!(ids::CAT, FourCC(*b"CAT "));
+//! assert!(Chunk::empty(ids::DATA).id() == "data");
+//! ```
+use crate::FourCC;
+
+// containers.
+pub const RIFF: FourCC = FourCC(*b"RIFF");
+pub const RF64: FourCC = FourCC(*b"RF64");
+pub const LIST: FourCC = FourCC(*b"LIST");
+pub const FORM: FourCC = FourCC(*b"FORM");
+pub const CAT: FourCC = FourCC(*b"CAT ");
+pub const PROP: FourCC = FourCC(*b"PROP");
+
+// form types.
+pub const WAVE: FourCC = FourCC(*b"WAVE");
+pub const AVI: FourCC = FourCC(*b"AVI ");
+pub const AIFF: FourCC = FourCC(*b"AIFF");
+pub const AIFC: FourCC = FourCC(*b"AIFC");
+pub const ILBM: FourCC = FourCC(*b"ILBM");
+pub const INFO: FourCC = FourCC(*b"INFO");
+pub const ADTL: FourCC = FourCC(*b"adtl");
+pub const HDRL: FourCC = FourCC(*b"hdrl");
+pub const MOVI: FourCC = FourCC(*b"movi");
+
+// RIFF.
+pub const FMT: FourCC = FourCC(*b"fmt ");
+pub const DATA: FourCC = FourCC(*b"data");
+pub const FACT: FourCC = FourCC(*b"fact");
+pub const CUE: FourCC = FourCC(*b"cue ");
+pub const SMPL: FourCC = FourCC(*b"smpl");
+pub const DS64: FourCC = FourCC(*b"ds64");
+pub const IDX1: FourCC = FourCC(*b"idx1");
+pub const INAM: FourCC = FourCC(*b"INAM");
+pub const IART: FourCC = FourCC(*b"IART");
+pub const ICMT: FourCC = FourCC(*b"ICMT");
+pub const ICOP: FourCC = FourCC(*b"ICOP");
+pub const ICRD: FourCC = FourCC(*b"ICRD");
+pub const ISFT: FourCC = FourCC(*b"ISFT");
+
+// AIFF and IFF.
+pub const COMM: FourCC = FourCC(*b"COMM");
+pub const SSND: FourCC = FourCC(*b"SSND");
+pub const MARK: FourCC = FourCC(*b"MARK");
+pub const INST: FourCC = FourCC(*b"INST");
+pub const NAME: FourCC = FourCC(*b"NAME");
+pub const AUTH: FourCC = FourCC(*b"AUTH");
+pub const ANNO: FourCC = FourCC(*b"ANNO");
+pub const BMHD: FourCC = FourCC(*b"BMHD");
+pub const CMAP: FourCC = FourCC(*b"CMAP");
+pub const BODY: FourCC = FourCC(*b"BODY");
+
+// filler.
+pub const JUNK: FourCC = FourCC(*b"JUNK");
+pub const PAD: FourCC = FourCC(*b"PAD ");
+pub const FLLR: FourCC = FourCC(*b"FLLR");
@@ -0,0 +1,195 @@
+use crate::{FourCC, Endianness, Alignment};
+
+/// Order of the two fields of a chunk header. IFF and RIFF put the id
+/// first, whereas PNG puts the length before the type.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum FieldOrder { #[default] IdThenSize, SizeThenId }
+
+/// Checksum which trails the body of each chunk, covering its id and
+/// body but not its size field.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum Checksum { #[default] None, Crc32 }
+
+/// Describes how each chunk is framed: the order of the header fields,
+/// the byte-order of its size, the boundary it's padded to and which
+/// checksum follows its body. `Decoder` and `Encoder` are generic over
+/// it, so the same machinery reads and writes IFF, RIFF or PNG.
+///
+/// ```
+/// use iffc::{Decoder, Encoder, Chunk, FourCC, PngLayout};
+///
+/// // the IHDR chunk of a 1x1 RGBA image, after the 8-byte signature.
+/// let ihdr = b"\x00\x00\x00\x0DIHDR\
+///              \x00\x00\x00\x01\x00\x00\x00\x01\x08\x06\x00\x00\x00\
+///              \x1F\x15\xC4\x89";
+///
+/// let chunk = Decoder::new(&ihdr[..])
+///     .with_layout(PngLayout)
+///     .next().unwrap().unwrap();
+///
+/// assert!(chunk.id() == "IHDR");
+/// assert_eq!(chunk.1.len(), 13);
+///
+/// // the encoder puts the very same CRC back.
+/// let mut enc = Encoder::new(Vec::new()).with_layout(PngLayout);
+/// enc.write_chunk(&chunk).unwrap();
+/// assert_eq!(enc.into_inner(), &ihdr[..]);
+/// ```
+pub trait ChunkLayout: Copy {
+    /// Byte-order of the size field.
+    fn endianness(&self) -> Endianness;
+
+    /// Boundary each chunk is padded to.
+    fn alignment(&self) -> Alignment;
+
+    /// Order of the id and the size in the header.
+    fn field_order(&self) -> FieldOrder { FieldOrder::IdThenSize }
+
+    /// Checksum following each body, none by default.
+    fn checksum(&self) -> Checksum { Checksum::None }
+}
+
+/// Microsoft RIFF: little-endian sizes, even-padded.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct RiffLayout;
+
+/// EA IFF and AIFF: big-endian sizes, even-padded.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct IffLayout;
+
+/// PNG: the big-endian length comes before the type, nothing is padded
+/// and each body is followed by the CRC-32 of its type and data.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct PngLayout;
+
+impl ChunkLayout for RiffLayout {
+    fn endianness(&self) -> Endianness { Endianness::Little }
+    fn alignment(&self) -> Alignment { Alignment::Two }
+}
+
+impl ChunkLayout for IffLayout {
+    fn endianness(&self) -> Endianness { Endianness::Big }
+    fn alignment(&self) -> Alignment { Alignment::Two }
+}
+
+impl ChunkLayout for PngLayout {
+    fn endianness(&self) -> Endianness { Endianness::Big }
+    fn alignment(&self) -> Alignment { Alignment::None }
+    fn field_order(&self) -> FieldOrder { FieldOrder::SizeThenId }
+    fn checksum(&self) -> Checksum { Checksum::Crc32 }
+}
+
+/// A layout chosen at runtime, which is what `Decoder::new` and
+/// `Encoder::new` use. It's RIFF unless configured otherwise, either
+/// field by field or through `with_endianness` and `with_alignment`.
+///
+/// Whatever the layout, what the `Encoder` writes decodes back to the
+/// very same chunks, empty and odd-length bodies included:
+/// ```
+/// use iffc::{Decoder, Encoder, Chunk, FourCC, Layout, Alignment, DecodeError};
+///
+/// // xorshift, so the "arbitrary" chunks are the same on every run.
+/// let mut seed = 0x2545F491u32;
+/// let mut next = move || { seed ^= seed << 13; seed ^= seed >> 17; seed ^= seed << 5; seed };
+///
+/// let layouts = [
+///     Layout::RIFF,
+///     Layout::IFF,
+///     Layout::PNG,
+///     Layout { alignment: Alignment::Four, ..Layout::RIFF },
+///     Layout { alignment: Alignment::None, ..Layout::IFF }
+/// ];
+///
+/// for layout in layouts {
+///     let chunks = (0..200).map(|_| {
+///         let len = (next() % 70) as usize;
+///         Chunk(FourCC(next().to_le_bytes()), (0..len).map(|_| next() as u8).collect())
+///     }).collect::<Vec<Chunk>>();
+///
+///     let mut enc = Encoder::new(Vec::new()).with_layout(layout);
+///     enc.write_all(chunks.iter().cloned())?;
+///     let out = enc.into_inner();
+///
+///     let back = Decoder::new(&out[..]).with_layout(layout).collect::<Result<Vec<Chunk>, _>>()?;
+///     assert_eq!(back, chunks);
+/// }
+///
+/// // the largest size a header can declare, allocated only as the bytes arrive.
+/// let huge = b"data\xFF\xFF\xFF\xFF";
+/// let mut dec = Decoder::new(&huge[..]).with_max_chunk_size(u32::MAX);
+/// assert!(matches!(dec.next(), Some(Err(DecodeError::TruncatedBody { expected: u32::MAX, got: 0, .. }))));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Layout {
+    pub endianness: Endianness,
+    pub alignment: Alignment,
+    pub field_order: FieldOrder,
+    pub checksum: Checksum
+}
+
+impl Layout {
+    pub const RIFF: Self = Self {
+        endianness: Endianness::Little,
+        alignment: Alignment::Two,
+        field_order: FieldOrder::IdThenSize,
+        checksum: Checksum::None
+    };
+
+    pub const IFF: Self = Self { endianness: Endianness::Big, ..Self::RIFF };
+
+    pub const PNG: Self = Self {
+        endianness: Endianness::Big,
+        alignment: Alignment::None,
+        field_order: FieldOrder::SizeThenId,
+        checksum: Checksum::Crc32
+    };
+}
+
+impl ChunkLayout for Layout {
+    fn endianness(&self) -> Endianness { self.endianness }
+    fn alignment(&self) -> Alignment { self.alignment }
+    fn field_order(&self) -> FieldOrder { self.field_order }
+    fn checksum(&self) -> Checksum { self.checksum }
+}
+
+/// Splits a chunk header into its id and its size, the way `layout`
+/// frames them.
+pub(crate) fn parse_header<L: ChunkLayout>(layout: &L, header: &[u8; 8]) -> (FourCC, u32) {
+    let mut swapped = [0u8; 8];
+
+    let header = match layout.field_order() {
+        FieldOrder::IdThenSize => header,
+        FieldOrder::SizeThenId => {
+            swapped[..4].copy_from_slice(&header[4..]);
+            swapped[4..].copy_from_slice(&header[..4]);
+            &swapped
+        }
+    };
+
+    crate::parse_header(header, layout.endianness())
+}
+
+/// Makes the header of a chunk with `id` and a body of `size` bytes.
+#[cfg(feature = "std")]
+pub(crate) fn make_header<L: ChunkLayout>(layout: &L, id: FourCC, size: u32) -> [u8; 8] {
+    let size = layout.endianness().write_u32(size);
+    let mut header = [0u8; 8];
+
+    let (id_at, size_at) = match layout.field_order() {
+        FieldOrder::IdThenSize => (0, 4),
+        FieldOrder::SizeThenId => (4, 0)
+    };
+
+    header[id_at..id_at + 4].copy_from_slice(&id.0);
+    header[size_at..size_at + 4].copy_from_slice(&size);
+    header
+}
+
+/// Number of bytes of the checksum which follows each body.
+pub(crate) fn trailer_len<L: ChunkLayout>(layout: &L) -> usize {
+    match layout.checksum() {
+        Checksum::None  => 0,
+        Checksum::Crc32 => 4
+    }
+}
@@ -12,26 +12,106 @@
 //! 
 //! fn main() {
 //!     let inp = std::io::Cursor::new(b"RIFF\x04\x00\x00\x00WAVE");
-//!     let parser = Decoder::new(Box::new(inp));
+//!     let parser = Decoder::new(inp);
 //! 
-//!     for chk in parser
-//!     { println!("{:?}: {}", chk.0, chk.1.len()); }
+//!     for chk in parser {
+//!         let chk = chk.expect("malformed chunk");
+//!         println!("{:?}: {}", chk.0, chk.1.len());
+//!     }
+//! }
+//! ```
+//! 
+//! Files can be opened directly, they're read through a `BufReader`:
+//! ```no_run
+//! use iffc::Decoder;
+//! 
+//! fn main() -> std::io::Result<()> {
+//!     for chk in Decoder::open("song.wav")? {
+//!         println!("{}", chk.expect("malformed chunk"));
+//!     }
+//! 
+//!     Ok(())
 //! }
 //! ```
 //! 
 //! To encode chunks into a given writer:
 //! ```
-//! use iffc::{Encoder, Chunk};
+//! use iffc::{Encoder, Chunk, FourCC};
 //! 
 //! fn main() {
 //!     let out = std::io::Cursor::new(Vec::new());
-//!     let deparser = Encoder::new(Box::new(out));
+//!     let deparser = Encoder::new(out);
 //! 
-//!     deparser << Chunk(*b"RIFF", Box::new(*b"WAVE"));
+//!     deparser << Chunk(FourCC(*b"RIFF"), Box::new(*b"WAVE"));
 //! }
 //! ```
-use std::io::{Read, Write, IoSlice, IoSliceMut};
-use std::ops::{Shl};
+//! 
+//! # `no_std`
+//! Everything built on `std::io` is behind the `std` feature, which is on by default. With
+//! `default-features = false, features = ["alloc"]` the crate builds with `#![no_std]`, where
+//! what's left parses chunks out of in-memory buffers: `Chunk`, `FourCC`, `parse_slice`,
+//! `SliceDecoder`, `PushDecoder`, `Group`, the layouts, and the `riff`, `wav` and `aiff`
+//! helpers. Checksums are skipped rather than verified there.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("iffc needs at least the `alloc` feature");
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+use alloc::boxed::Box;
+use alloc::borrow::Cow;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+mod fourcc;
+mod error;
+#[cfg(feature = "std")] mod decoder;
+#[cfg(feature = "std")] mod encoder;
+mod group;
+#[cfg(feature = "std")] mod tree;
+#[cfg(feature = "std")] mod seek;
+mod slice;
+mod push;
+#[cfg(feature = "std")] mod grouping;
+#[cfg(feature = "std")] mod validate;
+#[cfg(feature = "std")] mod transform;
+#[cfg(feature = "std")] mod builder;
+mod layout;
+#[cfg(feature = "std")] mod crc;
+#[cfg(feature = "std")] mod wide;
+#[cfg(feature = "std")] mod tee;
+#[cfg(feature = "std")] mod stream;
+#[cfg(feature = "std")] pub mod rf64;
+#[cfg(feature = "std")] pub mod avi;
+pub mod riff;
+pub mod aiff;
+pub mod wav;
+pub mod compress;
+pub mod ids;
+
+pub use fourcc::{FourCC, InvalidFourCC};
+pub use error::{DecodeError, ChunkError, FormatError};
+#[cfg(feature = "std")] pub use decoder::{Decoder, Offsets, FilterId, Coalesce, MapBodies, Ordered, RegionDecoder, RawChunks, Digests};
+#[cfg(feature = "std")] pub use encoder::{Encoder, GroupWriter, ContainerWriter, ChunkBodyWriter, Encoded};
+pub use group::Group;
+#[cfg(feature = "std")] pub use tree::{TreeDecoder, Node};
+#[cfg(feature = "std")] pub use seek::{SeekDecoder, ChunkHeader};
+pub use slice::{parse_slice, BorrowedChunk, SliceDecoder};
+pub use push::PushDecoder;
+#[cfg(feature = "std")] pub use grouping::{GroupingDecoder, GroupHandle, Entry, Children, Paths};
+#[cfg(feature = "std")] pub use validate::{validate, ValidationReport, Issue};
+#[cfg(feature = "std")] pub use transform::transform;
+#[cfg(feature = "std")] pub use builder::{DecoderBuilder, EncoderBuilder};
+#[cfg(feature = "std")] pub use crc::{Crc32, ChunkDigest};
+#[cfg(feature = "std")] pub use wide::{EightCC, WideChunk, WideDecoder, WideEncoder};
+#[cfg(feature = "std")] pub use tee::{Tee, TeeDecoder};
+#[cfg(feature = "std")] pub use stream::StreamingDecoder;
+pub use layout::{ChunkLayout, Layout, RiffLayout, IffLayout, PngLayout, FieldOrder, Checksum};
 
 /// An IFF chunk represents a single segment of a complete IFF
 /// file. Note: Even though this structure is capable of stroing
@@ -39,58 +119,746 @@ use std::ops::{Shl};
 /// 
 /// `0` — four-byte identity of chunk.
 /// `1` — byte-data encapsulated inside it.
-#[derive(Debug, Eq, PartialEq)]
-pub struct Chunk(pub [u8; 4], pub Box<[u8]>);
-
-/// A structure which wraps a reader and parses IFF chunks and
-/// behaves like an iterator which yields `IFFChunk` until
-/// an entire-chunk can't be constructed.
-pub struct Decoder(Box<dyn Read>);
-impl Decoder
-{ pub fn new(r: Box<dyn Read>) -> Self { Self(r) } }
-
-/// A structure which wraps a writer and writes IFF chunks to it,
-/// by using `<<` (shift-left) with an RHS of type `IFFChunk`, also
-/// that operand can be chained.
-pub struct Encoder(Box<dyn Write>);
-impl Encoder
-{ pub fn new(w: Box<dyn Write>) -> Self { Self(w) } }
-
-impl Iterator for Decoder {
+///
+/// Both `Display` and `Debug` show the id as text, `Debug` previews
+/// no more than the first 16 bytes of the body in hex:
+/// ```
+/// use iffc::{Chunk, FourCC};
+///
+/// let chunk = Chunk(FourCC(*b"fmt "), vec![0xAB; 20].into());
+///
+/// assert_eq!(chunk.to_string(), "\"fmt \" (20 bytes)");
+/// assert_eq!(format!("{:?}", chunk), "Chunk { id: \"fmt \", len: 20, data: \
+///     ab ab ab ab ab ab ab ab ab ab ab ab ab ab ab ab .. }");
+///
+/// // however large the body, while the body stays reachable as is.
+/// let large = Chunk(FourCC(*b"data"), vec![0x52; 1 << 20].into());
+/// assert!(format!("{:?}", large).len() < 100);
+/// assert!(format!("{:?}", large).contains("len: 1048576"));
+/// assert_eq!(large.1.len(), 1 << 20);
+/// ```
+///
+/// Chunks can be cloned, which copies their body, hashed and ordered
+/// by their id then body:
+/// ```
+/// use std::collections::HashSet;
+/// use iffc::{Chunk, FourCC};
+///
+/// let chunk = Chunk(FourCC(*b"data"), Box::new(*b"abc"));
+/// let copy = chunk.clone();
+/// assert_eq!(copy, chunk);
+///
+/// let unique = vec![chunk, copy, Chunk(FourCC(*b"fmt "), Box::new([]))]
+///     .into_iter()
+///     .collect::<HashSet<_>>();
+/// assert_eq!(unique.len(), 2);
+///
+/// let mut ids = vec![FourCC(*b"fmt "), FourCC(*b"LIST"), FourCC(*b"data")];
+/// ids.sort();
+/// assert_eq!(ids, [FourCC(*b"LIST"), FourCC(*b"data"), FourCC(*b"fmt ")]);
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Chunk(pub FourCC, pub Box<[u8]>);
+
+impl Chunk {
+    /// Makes a chunk out of `id` and `data`, failing if the body is too
+    /// large for its size to fit the `u32` of a chunk header.
+    /// ```
+    /// use iffc::{Chunk, ChunkError};
+    ///
+    /// let chunk = Chunk::new(*b"data", vec![1, 2, 3]).unwrap();
+    /// assert!(chunk.id() == "data");
+    ///
+    /// if cfg!(target_pointer_width = "64") {
+    ///     let huge = vec![0u8; u32::MAX as usize + 1];
+    ///     assert_eq!(Chunk::new(*b"data", huge), Err(ChunkError::TooLarge { len: 1 << 32 }));
+    /// }
+    /// ```
+    pub fn new<I: Into<FourCC>, D: Into<Box<[u8]>>>(id: I, data: D) -> Result<Self, ChunkError> {
+        let chunk = Self(id.into(), data.into());
+        chunk.checked_body()?;
+        Ok(chunk)
+    }
+
+    /// The body, as long as its length fits the `u32` size field of a
+    /// header, which a chunk built through its public fields may not.
+    /// The `Encoder` checks each chunk with it before writing anything.
+    /// ```
+    /// use std::io::ErrorKind;
+    /// use iffc::{Chunk, Encoder, FourCC, ChunkError};
+    ///
+    /// assert_eq!(Chunk(FourCC(*b"data"), Box::new(*b"ab")).checked_body(), Ok(&b"ab"[..]));
+    ///
+    /// if cfg!(target_pointer_width = "64") {
+    ///     let huge = Chunk(FourCC(*b"data"), vec![0u8; u32::MAX as usize + 1].into());
+    ///     assert_eq!(huge.checked_body(), Err(ChunkError::TooLarge { len: 1 << 32 }));
+    ///
+    ///     // rather than a size field truncated to 0.
+    ///     let mut enc = Encoder::new(Vec::new());
+    ///     assert_eq!(enc.write_chunk(&huge).unwrap_err().kind(), ErrorKind::InvalidInput);
+    ///     assert!(enc.into_inner().is_empty());
+    /// }
+    /// ```
+    pub fn checked_body(&self) -> Result<&[u8], ChunkError> {
+        match u32::try_from(self.1.len()) {
+            Ok(_) => Ok(&self.1),
+            Err(_) => Err(ChunkError::TooLarge { len: self.1.len() })
+        }
+    }
+
+    /// The sub-chunks of a container, borrowed out of its body after
+    /// the form type and read as RIFF unless `with_layout` is called on
+    /// the decoder returned. Fails with a `DecodeError::NotContainer` if
+    /// the id isn't one known to `Group::is_container`, or with a
+    /// `DecodeError::UnexpectedEof` if the body can't hold a form type.
+    /// ```
+    /// use iffc::{Chunk, Decoder, BorrowedChunk, FourCC, Layout, DecodeError};
+    ///
+    /// let inp = b"LIST\x1A\x00\x00\x00INFO\
+    ///             INAM\x03\x00\x00\x00abc\0\
+    ///             IART\x02\x00\x00\x00de";
+    ///
+    /// let list = Decoder::new(&inp[..]).next().unwrap()?;
+    /// assert_eq!(list.children()?.collect::<Result<Vec<_>, _>>()?, [
+    ///     BorrowedChunk(FourCC(*b"INAM"), b"abc"),
+    ///     BorrowedChunk(FourCC(*b"IART"), b"de")
+    /// ]);
+    ///
+    /// let form = Chunk(FourCC(*b"FORM"), Box::new(*b"AIFFCOMM\x00\x00\x00\x01x\0"));
+    /// assert_eq!(form.children()?.with_layout(Layout::IFF).next().unwrap()?, BorrowedChunk(FourCC(*b"COMM"), b"x"));
+    ///
+    /// assert!(matches!(Chunk(FourCC(*b"data"), Box::new([])).children(), Err(DecodeError::NotContainer { .. })));
+    /// assert!(matches!(Chunk::empty(*b"LIST").children(), Err(DecodeError::UnexpectedEof { expected: 4, got: 0 })));
+    /// # Ok::<(), DecodeError>(())
+    /// ```
+    pub fn children(&self) -> Result<SliceDecoder<'_>, DecodeError> {
+        if !Group::is_container(self.0)
+        { return Err(DecodeError::NotContainer { id: self.0, offset: 0 }) }
+
+        match self.1.get(4..) {
+            Some(children) => Ok(SliceDecoder::new(children)),
+            None => Err(DecodeError::UnexpectedEof { expected: 4, got: self.1.len() })
+        }
+    }
+
+    /// Four-byte identity of the chunk.
+    /// ```
+    /// use iffc::{Chunk, FourCC};
+    ///
+    /// let chunk = Chunk(FourCC(*b"fmt "), Box::new([]));
+    /// assert!(chunk.id() == "fmt ");
+    /// ```
+    pub fn id(&self) -> FourCC { self.0 }
+
+    /// The body of the chunk, also reachable through `AsRef<[u8]>`.
+    /// ```
+    /// use iffc::{Chunk, FourCC};
+    ///
+    /// let mut chunk = Chunk(FourCC(*b"INAM"), Box::new(*b"title"));
+    /// assert_eq!(chunk.data(), b"title");
+    ///
+    /// chunk.data_mut()[0] = b'T';
+    /// assert_eq!(chunk.as_ref(), b"Title");
+    /// assert_eq!(&chunk.into_data()[..], b"Title");
+    /// ```
+    pub fn data(&self) -> &[u8] { &self.1 }
+
+    /// Mutable reference to the body, which can't be resized through it.
+    pub fn data_mut(&mut self) -> &mut [u8] { &mut self.1 }
+
+    /// Unwraps the body.
+    pub fn into_data(self) -> Box<[u8]> { self.1 }
+
+    /// A chunk with a body of no bytes, such as a marker. It's framed
+    /// as a lone header, since an empty body takes no padding.
+    /// ```
+    /// use iffc::{Chunk, Decoder, Encoder, FourCC};
+    ///
+    /// let mark = Chunk::empty(*b"mark");
+    /// assert!(mark.is_empty());
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// enc.write_chunk(&mark)?;
+    /// enc.write_chunk(&Chunk(FourCC(*b"data"), Box::new(*b"a")))?;
+    ///
+    /// let out = enc.into_inner();
+    /// assert_eq!(out, b"mark\x00\x00\x00\x00data\x01\x00\x00\x00a\0");
+    /// assert_eq!(Decoder::new(&out[..]).collect::<Result<Vec<Chunk>, _>>().unwrap(),
+    ///     [mark, Chunk(FourCC(*b"data"), Box::new(*b"a"))]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn empty<I: Into<FourCC>>(id: I) -> Self
+    { Self(id.into(), Box::new([])) }
+
+    /// Whether the body holds no bytes.
+    pub fn is_empty(&self) -> bool { self.1.is_empty() }
+
+    /// Whether both chunks have the same id and the same content. Unlike
+    /// `==`, a body which is one of odd length with a zero pad byte
+    /// appended, as some tools write them, equals the unpadded one. The
+    /// bodies a `Decoder` reads never hold the padding, so chunks read
+    /// from inputs padded differently compare equal as well.
+    /// ```
+    /// use iffc::{Chunk, Decoder, Alignment};
+    ///
+    /// let zero = Decoder::new(&b"note\x03\x00\x00\x00odd\0"[..]).next().unwrap().unwrap();
+    /// let ones = Decoder::new(&b"note\x03\x00\x00\x00odd\xFF"[..]).next().unwrap().unwrap();
+    /// let four = Decoder::new(&b"note\x03\x00\x00\x00odd\0"[..])
+    ///     .with_alignment(Alignment::Four)
+    ///     .next().unwrap().unwrap();
+    ///
+    /// assert!(zero.content_eq(&ones) && zero.content_eq(&four));
+    /// assert!(!zero.content_eq(&Chunk::from((*b"note", &b"od"[..]))));
+    ///
+    /// // the pad byte kept in the body by another tool.
+    /// assert!(zero.content_eq(&Chunk::from((*b"note", &b"odd\0"[..]))));
+    /// assert!(!zero.content_eq(&Chunk::from((*b"note", &b"odd\xFF"[..]))));
+    /// assert!(!Chunk::from((*b"note", &b"ev"[..])).content_eq(&Chunk::from((*b"note", &b"ev\0"[..]))));
+    /// assert_ne!(zero, Chunk::from((*b"note", &b"odd\0"[..])));
+    /// ```
+    pub fn content_eq(&self, other: &Chunk) -> bool {
+        // whether `a` is `b`, of odd length, followed by a pad byte.
+        let padded = |a: &[u8], b: &[u8]|
+            b.len() % 2 == 1 && a.len() == b.len() + 1 && a.starts_with(b) && a[b.len()] == 0;
+
+        self.0 == other.0 && (self.1 == other.1 || padded(&self.1, &other.1) || padded(&other.1, &self.1))
+    }
+
+    /// The same chunk under another id.
+    pub fn with_id<I: Into<FourCC>>(mut self, id: I) -> Self
+    { self.0 = id.into(); self }
+
+    /// Replaces the body with what `f` makes out of it, which is checked
+    /// the same way as by `Chunk::new`.
+    /// ```
+    /// use iffc::{Chunk, FourCC};
+    ///
+    /// let strip_nuls = |b: Box<[u8]>| b.iter().copied().filter(|&b| b != 0).collect();
+    ///
+    /// let chunk = Chunk(FourCC(*b"INAM"), Box::new(*b"Title\0\0"))
+    ///     .with_id(*b"ICMT")
+    ///     .map_data(strip_nuls)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(chunk, Chunk(FourCC(*b"ICMT"), Box::new(*b"Title")));
+    /// ```
+    pub fn map_data<F: FnOnce(Box<[u8]>) -> Box<[u8]>>(self, f: F) -> Result<Self, ChunkError>
+    { Self::new(self.0, f(self.1)) }
+
+    /// Splits the body into pieces of at most `max` bytes, each making
+    /// a chunk of the same id (see `Split::with_continuation_id`), the
+    /// last one holding what's left. A body no longer than `max` comes
+    /// out unchanged. Fails with `ChunkError::ZeroSplit` if `max` is 0.
+    /// ```
+    /// use iffc::{Chunk, FourCC, ChunkError};
+    ///
+    /// let body = (0..1000).map(|n| n as u8).collect::<Vec<_>>();
+    /// let pieces = Chunk(FourCC(*b"data"), body.clone().into())
+    ///     .split(256).unwrap()
+    ///     .collect::<Vec<Chunk>>();
+    ///
+    /// assert_eq!(pieces.iter().map(|c| c.1.len()).collect::<Vec<_>>(), [256, 256, 256, 232]);
+    /// assert!(pieces.iter().all(|c| c.0 == "data"));
+    /// assert_eq!(pieces.iter().flat_map(|c| c.1.iter().copied()).collect::<Vec<_>>(), body);
+    ///
+    /// let small = Chunk(FourCC(*b"note"), Box::new(*b"abc"));
+    /// assert_eq!(small.clone().split(256).unwrap().collect::<Vec<Chunk>>(), [small.clone()]);
+    /// assert_eq!(small.split(0).err(), Some(ChunkError::ZeroSplit));
+    /// ```
+    pub fn split(self, max: usize) -> Result<Split, ChunkError> {
+        if max == 0 { return Err(ChunkError::ZeroSplit) }
+        Ok(Split { id: self.0, continuation: self.0, body: self.1, pos: 0, max, done: false })
+    }
+
+    /// The id as text, borrowed if it is printable ASCII and escaped as
+    /// by `FourCC`'s `Display` otherwise.
+    /// ```
+    /// use iffc::{Chunk, FourCC};
+    ///
+    /// assert_eq!(Chunk(FourCC(*b"fmt "), Box::new([])).id_str(), "fmt ");
+    /// assert_eq!(Chunk(FourCC(*b"\x00abc"), Box::new([])).id_str(), "\\x00abc");
+    /// ```
+    pub fn id_str(&self) -> Cow<'_, str> {
+        match core::str::from_utf8(&self.0 .0) {
+            Ok(s) if self.0.is_printable() && !s.contains('\\') => Cow::Borrowed(s),
+            _ => Cow::Owned(self.0.to_string())
+        }
+    }
+
+    /// Formats no more than the first `max` bytes of the body as the
+    /// lines of `hexdump -C`: the offset, sixteen bytes in hex and
+    /// those of them which are printable ASCII. The last line holds the
+    /// length of the body dumped, followed by how many bytes were left
+    /// out if any.
+    /// ```
+    /// use iffc::{Chunk, FourCC};
+    ///
+    /// let chunk = Chunk(FourCC(*b"data"), Box::new(*b"RIFF\x24\x00\x00\x00WAVEfmt \x10\x00"));
+    ///
+    /// assert_eq!(chunk.hexdump(usize::MAX).to_string(), "\
+    ///     00000000  52 49 46 46 24 00 00 00  57 41 56 45 66 6d 74 20  |RIFF$...WAVEfmt |\n\
+    ///     00000010  10 00                                             |..|\n\
+    ///     00000012\n");
+    ///
+    /// assert_eq!(chunk.hexdump(4).to_string(), "\
+    ///     00000000  52 49 46 46                                       |RIFF|\n\
+    ///     00000004  (14 more bytes)\n");
+    /// ```
+    pub fn hexdump(&self, max: usize) -> HexDump<'_>
+    { HexDump { data: &self.1, max } }
+
+    /// Reads the fixed-layout fields of the body one after another, the
+    /// integers and floats in `endianness`. Reading past the end of the
+    /// body fails with `FormatError::TooShort`, telling how long it
+    /// would've had to be, and leaves the cursor where it was.
+    /// ```
+    /// use iffc::{Chunk, Endianness, FourCC, FormatError};
+    ///
+    /// let chunk = Chunk(FourCC(*b"hdr "), Box::new(*b"\x01\x00\xFE\xFF\x44\xAC\x00\x00\x00\x00\xC0\x3FWAVExy"));
+    /// let mut f = chunk.fields(Endianness::Little);
+    ///
+    /// assert_eq!(f.u16()?, 1);
+    /// assert_eq!(f.i16()?, -2);
+    /// assert_eq!(f.u32()?, 44100);
+    /// assert_eq!(f.f32()?, 1.5);
+    /// assert_eq!(f.fourcc()?, FourCC(*b"WAVE"));
+    /// assert_eq!(f.bytes(2)?, b"xy");
+    ///
+    /// assert_eq!(f.u32(), Err(FormatError::TooShort { expected: 22, got: 18 }));
+    /// assert_eq!(f.remaining(), b"");
+    /// # Ok::<(), FormatError>(())
+    /// ```
+    pub fn fields(&self, endianness: Endianness) -> Fields<'_>
+    { Fields { data: &self.1, pos: 0, endianness } }
+
+    /// Reads the body in place, for parsing typed fields out of it or
+    /// decoding the sub-chunks of a container with a nested `Decoder`.
+    /// ```
+    /// use std::io::Read;
+    /// use iffc::{Chunk, FourCC};
+    ///
+    /// let fmt = Chunk(FourCC(*b"fmt "), Box::new(*b"\x01\x00\x02\x00\x44\xAC\x00\x00"));
+    /// let mut r = fmt.reader();
+    ///
+    /// let mut u16_buf = [0u8; 2];
+    /// let mut u32_buf = [0u8; 4];
+    ///
+    /// r.read_exact(&mut u16_buf)?;
+    /// assert_eq!(u16::from_le_bytes(u16_buf), 1); // PCM
+    /// r.read_exact(&mut u16_buf)?;
+    /// assert_eq!(u16::from_le_bytes(u16_buf), 2); // channels
+    /// r.read_exact(&mut u32_buf)?;
+    /// assert_eq!(u32::from_le_bytes(u32_buf), 44100);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn reader(&self) -> io::Cursor<&[u8]> { io::Cursor::new(&self.1) }
+
+    /// Like `reader`, but takes the body along with it.
+    /// ```
+    /// use iffc::{Chunk, Decoder, FourCC};
+    ///
+    /// let list = Chunk(FourCC(*b"LIST"), Box::new(*b"INFOINAM\x01\x00\x00\x00x\x00"));
+    ///
+    /// // the sub-chunks follow the form type.
+    /// let mut body = list.into_reader();
+    /// body.set_position(4);
+    ///
+    /// let names = Decoder::new(body).collect::<Result<Vec<Chunk>, _>>().unwrap();
+    /// assert_eq!(names, [Chunk(FourCC(*b"INAM"), Box::new(*b"x"))]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn into_reader(self) -> io::Cursor<Box<[u8]>> { io::Cursor::new(self.1) }
+
+    /// Number of bytes the chunk takes up once encoded as RIFF, that is
+    /// its 8-byte header, the body and the pad byte of an odd body.
+    pub fn encoded_len(&self) -> usize
+    { 8 + self.1.len() + Alignment::Two.padding(self.1.len()) }
+
+    /// Encodes the chunk as RIFF (little-endian, even-aligned) into `w`,
+    /// exactly as an `Encoder` with the default settings would write it.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()>
+    { encoder::write_framed(w, self.0, &self.1, &RiffLayout, 0) }
+
+    /// Encodes the chunk as RIFF into a new buffer, see `write_to`.
+    /// Panics if the body is larger than `u32::MAX` bytes.
+    /// ```
+    /// use iffc::{Chunk, Encoder, FourCC};
+    ///
+    /// let chunk = Chunk(FourCC(*b"abc "), Box::new(*b"xyz"));
+    /// assert_eq!(chunk.to_bytes(), b"abc \x03\x00\x00\x00xyz\0");
+    /// assert_eq!(chunk.encoded_len(), 12);
+    ///
+    /// let mut enc = Encoder::new(Vec::new());
+    /// enc.write_chunk(&chunk).unwrap();
+    /// assert_eq!(enc.into_inner(), chunk.to_bytes());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        self.write_to(&mut buf).expect("chunk body larger than 4 GiB");
+        buf
+    }
+}
+
+impl AsRef<[u8]> for Chunk
+{ fn as_ref(&self) -> &[u8] { &self.1 } }
+
+/// Parses a buffer holding exactly one RIFF (little-endian,
+/// even-aligned) chunk, the reverse of `Chunk::to_bytes`. The pad byte
+/// of an odd body may be left out, but nothing else may follow it.
+/// ```
+/// use std::convert::TryFrom;
+/// use iffc::{Chunk, FourCC, DecodeError};
+///
+/// let chunk = Chunk(FourCC(*b"note"), Box::new(*b"odd"));
+/// let bytes = chunk.to_bytes();
+/// assert_eq!(Chunk::try_from(&bytes[..])?, chunk);
+/// assert_eq!(Chunk::try_from(&bytes[..11])?, chunk);
+///
+/// assert!(matches!(Chunk::try_from(&bytes[..5]),
+///     Err(DecodeError::UnexpectedEof { expected: 8, got: 5 })));
+/// assert!(matches!(Chunk::try_from(&bytes[..10]),
+///     Err(DecodeError::TruncatedBody { offset: 0, expected: 3, got: 2, .. })));
+///
+/// let mut junk = bytes.clone();
+/// junk.extend_from_slice(b"xyz");
+/// assert!(matches!(Chunk::try_from(&junk[..]), Err(DecodeError::TrailingBytes { count: 3 })));
+/// # Ok::<(), DecodeError>(())
+/// ```
+impl TryFrom<&[u8]> for Chunk {
+    type Error = DecodeError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (chunk, rest) = match slice::split_chunk(bytes, &RiffLayout) {
+            Some(split) => split,
+            None if bytes.len() < 8 =>
+                return Err(DecodeError::UnexpectedEof { expected: 8, got: bytes.len() }),
+            None => {
+                let mut header = [0u8; 8];
+                header.copy_from_slice(&bytes[..8]);
+
+                let (id, size) = parse_header(&header, Endianness::Little);
+                return Err(DecodeError::TruncatedBody { id, offset: 0, expected: size, got: bytes.len() - 8 })
+            }
+        };
+
+        if !rest.is_empty()
+        { return Err(DecodeError::TrailingBytes { count: rest.len() }) }
+
+        Ok(chunk.into())
+    }
+}
+
+/// Builds a chunk out of an id and a body, the body being copied when
+/// borrowed. An id given as a string has to be four bytes long.
+/// ```
+/// use std::convert::TryFrom;
+/// use iffc::{Chunk, FourCC, InvalidFourCC};
+///
+/// let chunk = Chunk(FourCC(*b"data"), Box::new(*b"abc"));
+///
+/// assert_eq!(Chunk::from((*b"data", vec![b'a', b'b', b'c'])), chunk);
+/// assert_eq!(Chunk::from((*b"data", &b"abc"[..])), chunk);
+/// assert_eq!(Chunk::from((FourCC(*b"data"), &b"abc"[..])), chunk);
+///
+/// assert_eq!(Chunk::try_from(("data", &b"abc"[..])), Ok(chunk));
+/// assert_eq!(Chunk::try_from(("dat", &b"abc"[..])), Err(InvalidFourCC(3)));
+/// assert_eq!(Chunk::try_from(("datum", &b""[..])), Err(InvalidFourCC(5)));
+/// ```
+impl<I: Into<FourCC>> From<(I, Vec<u8>)> for Chunk
+{ fn from((id, body): (I, Vec<u8>)) -> Self { Self(id.into(), body.into_boxed_slice()) } }
+
+impl<I: Into<FourCC>> From<(I, &[u8])> for Chunk
+{ fn from((id, body): (I, &[u8])) -> Self { Self(id.into(), body.into()) } }
+
+impl TryFrom<(&str, &[u8])> for Chunk {
+    type Error = InvalidFourCC;
+
+    fn try_from((id, body): (&str, &[u8])) -> Result<Self, InvalidFourCC>
+    { Ok(Self(FourCC::try_from(id)?, body.into())) }
+}
+
+/// Iterator over the pieces of a chunk, see `Chunk::split`.
+#[derive(Debug, Clone)]
+pub struct Split {
+    id: FourCC,
+    continuation: FourCC,
+    body: Box<[u8]>,
+    pos: usize,
+    max: usize,
+    done: bool
+}
+
+impl Split {
+    /// Gives the pieces after the first one `id`, rather than the id of
+    /// the chunk being split.
+    /// ```
+    /// use iffc::{Chunk, FourCC};
+    ///
+    /// let ids = Chunk(FourCC(*b"data"), Box::new(*b"abcde"))
+    ///     .split(2).unwrap()
+    ///     .with_continuation_id(*b"cont")
+    ///     .map(|c| c.0)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(ids, [FourCC(*b"data"), FourCC(*b"cont"), FourCC(*b"cont")]);
+    /// ```
+    pub fn with_continuation_id<I: Into<FourCC>>(mut self, id: I) -> Self
+    { self.continuation = id.into(); self }
+}
+
+impl Iterator for Split {
     type Item = Chunk;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut id   = [0u8; 4];
-        let mut size = [0u8; 4];
-
-        self.0.read_vectored(&mut [
-            IoSliceMut::new(&mut id),
-            IoSliceMut::new(&mut size)
-        ]).ok()?;
-
-        let size = u32::from_le_bytes(size) as usize;
-        let mut data = vec![0u8; size];
-        
-        match self.0.read(&mut data) {
-            Ok(s) => if size != s { return None },
-            Err(_) => { return None }
+    fn next(&mut self) -> Option<Chunk> {
+        if self.done { return None }
+
+        let id = if self.pos == 0 { self.id } else { self.continuation };
+        let end = self.body.len().min(self.pos.saturating_add(self.max));
+        self.done = end == self.body.len();
+
+        // a body no longer than a piece is handed out without a copy.
+        let piece = match (self.pos, self.done) {
+            (0, true) => core::mem::take(&mut self.body),
+            _ => self.body[self.pos..end].into()
         };
 
-        Some(Chunk(id, data.into_boxed_slice()))
+        self.pos = end;
+        Some(Chunk(id, piece))
+    }
+}
+
+/// Sorts `chunks` by their id, those with the same id keeping their
+/// order. Meant for reproducible output of formats where the order of
+/// chunks doesn't matter, it isn't done by anything else in the crate
+/// as many formats depend on it, such as WAV where `fmt ` must come
+/// before `data`.
+/// ```
+/// use iffc::{canonicalize, Chunk, FourCC};
+///
+/// let chunk = |id: &[u8; 4], body: &[u8]| Chunk(FourCC(*id), body.into());
+///
+/// let mut chunks = [chunk(b"name", b"b"), chunk(b"auth", b"x"), chunk(b"name", b"a"), chunk(b"AUTH", b"")];
+/// canonicalize(&mut chunks);
+///
+/// assert_eq!(chunks, [chunk(b"AUTH", b""), chunk(b"auth", b"x"), chunk(b"name", b"b"), chunk(b"name", b"a")]);
+/// assert!(chunk(b"name", b"a") < chunk(b"name", b"b"));
+/// ```
+pub fn canonicalize(chunks: &mut [Chunk])
+{ chunks.sort_by_key(|c| c.0) }
+
+/// A chunk along with the pad bytes which actually followed its body,
+/// so that it can be written back byte for byte even if they weren't
+/// zero, or were missing. See `Decoder::raw` and `Encoder::write_raw`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RawChunk {
+    pub chunk: Chunk,
+    pub pad: Box<[u8]>
+}
+
+impl fmt::Display for Chunk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    { write!(f, "{:?} ({} bytes)", self.0, self.1.len()) }
+}
+
+/// Hexdump of the start of a body, see `Chunk::hexdump`.
+#[derive(Debug, Clone, Copy)]
+pub struct HexDump<'a> {
+    data: &'a [u8],
+    max: usize
+}
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shown = &self.data[..self.data.len().min(self.max)];
+
+        for (n, line) in shown.chunks(16).enumerate() {
+            write!(f, "{:08x} ", n * 16)?;
+
+            for i in 0..16 {
+                if i % 8 == 0 { f.write_str(" ")? }
+
+                match line.get(i) {
+                    Some(b) => write!(f, "{:02x} ", b)?,
+                    None => f.write_str("   ")?
+                }
+            }
+
+            f.write_str(" |")?;
+            for &b in line {
+                let c = if (0x20..=0x7E).contains(&b) { b as char } else { '.' };
+                write!(f, "{}", c)?;
+            }
+            f.write_str("|\n")?;
+        }
+
+        write!(f, "{:08x}", shown.len())?;
+        if shown.len() < self.data.len()
+        { write!(f, "  ({} more bytes)", self.data.len() - shown.len())? }
+        f.write_str("\n")
+    }
+}
+
+/// Cursor over the fields of a body, see `Chunk::fields`.
+#[derive(Debug, Clone)]
+pub struct Fields<'a> {
+    data: &'a [u8],
+    pos: usize,
+    endianness: Endianness
+}
+
+impl<'a> Fields<'a> {
+    /// Next `n` bytes of the body, `FormatError::TooShort` if fewer are
+    /// left. Nothing is read on failure, by this or the other methods.
+    pub fn bytes(&mut self, n: usize) -> Result<&'a [u8], FormatError> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.data.len())
+            .ok_or(FormatError::TooShort { expected: self.pos.saturating_add(n), got: self.data.len() })?;
+
+        let b = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(b)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], FormatError> {
+        let mut b = [0u8; N];
+        b.copy_from_slice(self.bytes(N)?);
+
+        if self.endianness == Endianness::Big { b.reverse() }
+        Ok(b)
+    }
+
+    /// Next two bytes as an unsigned integer in the byte order given to
+    /// `Chunk::fields`, `FormatError::TooShort` if fewer are left.
+    pub fn u16(&mut self) -> Result<u16, FormatError> { self.array().map(u16::from_le_bytes) }
+
+    /// Same as `u16`, but signed.
+    pub fn i16(&mut self) -> Result<i16, FormatError> { self.array().map(i16::from_le_bytes) }
+
+    /// Next four bytes as an unsigned integer in the byte order given to
+    /// `Chunk::fields`, `FormatError::TooShort` if fewer are left.
+    pub fn u32(&mut self) -> Result<u32, FormatError> { self.array().map(u32::from_le_bytes) }
+
+    /// Next four bytes as an IEEE 754 float in the byte order given to
+    /// `Chunk::fields`, `FormatError::TooShort` if fewer are left.
+    pub fn f32(&mut self) -> Result<f32, FormatError> { self.array().map(f32::from_le_bytes) }
+
+    /// Next four bytes as an id, which aren't byte-swapped.
+    pub fn fourcc(&mut self) -> Result<FourCC, FormatError> {
+        let mut b = [0u8; 4];
+        b.copy_from_slice(self.bytes(4)?);
+        Ok(FourCC(b))
     }
+
+    /// Number of bytes read so far.
+    pub fn position(&self) -> usize { self.pos }
+
+    /// The bytes not read yet.
+    pub fn remaining(&self) -> &'a [u8] { &self.data[self.pos..] }
 }
 
-impl Shl<Chunk> for Encoder {
-    type Output = Option<Self>;
-    
-    fn shl(self, chunk: Chunk) -> Option<Self> {
-        sel.0.write_vectored(&[
-            IoSlice::new(&chunk.0),
-            IoSlice::new(&(chunk.1.len() as u32)
-                            .to_le_bytes()[..]),
-            IoSlice::new(&chunk.1)
-        ]).ok()?;
-        
-        Some(Self(self.0))
+/// Hex preview of the start of a body, see `Chunk`'s `Debug`.
+struct Preview<'a>(&'a [u8]);
+
+impl fmt::Debug for Preview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, b) in self.0.iter().take(16).enumerate() {
+            if i > 0 { f.write_str(" ")? }
+            write!(f, "{:02x}", b)?;
+        }
+
+        if self.0.len() > 16 { f.write_str(" ..")? }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Chunk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Chunk")
+            .field("id", &self.0)
+            .field("len", &self.1.len())
+            .field("data", &Preview(&self.1))
+            .finish()
+    }
+}
+
+/// Largest body a `Decoder` accepts unless configured otherwise.
+pub(crate) const DEFAULT_MAX_CHUNK_SIZE: u32 = 64 << 20;
+
+/// Byte-order of the size field of each chunk header. Microsoft RIFF
+/// stores it little-endian, whereas the original EA IFF and Apple AIFF
+/// store it big-endian.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum Endianness { #[default] Little, Big }
+
+impl Endianness {
+    fn read_u32(self, b: [u8; 4]) -> u32 {
+        match self {
+            Self::Little => u32::from_le_bytes(b),
+            Self::Big    => u32::from_be_bytes(b)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn write_u32(self, n: u32) -> [u8; 4] {
+        match self {
+            Self::Little => n.to_le_bytes(),
+            Self::Big    => n.to_be_bytes()
+        }
+    }
+}
+
+/// Splits a chunk header into its id and its size, read in `endianness`.
+fn parse_header(header: &[u8; 8], endianness: Endianness) -> (FourCC, u32) {
+    let mut id   = [0u8; 4];
+    let mut size = [0u8; 4];
+
+    id.copy_from_slice(&header[..4]);
+    size.copy_from_slice(&header[4..]);
+
+    (FourCC(id), endianness.read_u32(size))
+}
+
+/// Boundary to which each chunk is padded, so that the next one starts
+/// on an aligned offset. Pad bytes follow the body and aren't counted
+/// in its declared size. Standard IFF and RIFF pad to even offsets,
+/// some derived formats pad to multiples of four.
+///
+/// ```
+/// use iffc::{Encoder, Decoder, Chunk, FourCC, Alignment};
+///
+/// for &(alignment, pad) in &[(Alignment::None, 0), (Alignment::Two, 1), (Alignment::Four, 3)] {
+///     let body: &[u8] = if alignment == Alignment::Four { b"x" } else { b"xyz" };
+///     let chunks = vec![
+///         Chunk(FourCC(*b"odd "), body.into()),
+///         Chunk(FourCC(*b"even"), Box::new(*b"abcd"))
+///     ];
+///
+///     let mut enc = Encoder::new(Vec::new()).with_alignment(alignment);
+///     enc.extend(&chunks);
+///
+///     let out = enc.into_inner();
+///     assert_eq!(out.len(), 8 + body.len() + pad + 8 + 4);
+///     assert!(out[8 + body.len()..][..pad].iter().all(|&b| b == 0));
+///
+///     let back = Decoder::new(&out[..])
+///         .with_alignment(alignment)
+///         .collect::<Result<Vec<Chunk>, _>>()
+///         .unwrap();
+///     assert_eq!(back, chunks);
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum Alignment { None, #[default] Two, Four }
+
+impl Alignment {
+    /// Number of pad bytes which follow a body of `len` bytes.
+    fn padding(self, len: usize) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Two  => len % 2,
+            Self::Four => (4 - len % 4) % 4
+        }
     }
 }
@@ -0,0 +1,180 @@
+use alloc::vec::Vec;
+use crate::{Chunk, Endianness, Alignment, FieldOrder, DecodeError, ChunkLayout, Layout};
+use crate::layout;
+use crate::slice::{split_header, body_len, split_chunk};
+
+/// A decoder which doesn't read from a stream by itself, rather bytes
+/// are pushed into it as they arrive and chunks are taken out of it
+/// once they are entire. This suits sources which hand out data in
+/// arbitrary pieces without blocking, such as sockets driven by an
+/// asynchronous runtime, since nothing is parsed until a full header,
+/// body and pad byte are buffered.
+///
+/// The chunks are framed exactly as with a `Decoder`, by any
+/// `ChunkLayout`, though checksums are skipped over without being
+/// verified, as with a `SliceDecoder`.
+///
+/// ```
+/// use iffc::{PushDecoder, Chunk, FourCC};
+///
+/// let mut dec = PushDecoder::new();
+///
+/// dec.push(b"abc \x03\x00");
+/// assert_eq!(dec.next_chunk().unwrap(), None);
+///
+/// dec.push(b"\x00\x00xyz");
+/// assert_eq!(dec.next_chunk().unwrap(), None); // waits for the pad byte.
+///
+/// dec.push(b"\0data\x02\x00\x00\x00de");
+/// assert_eq!(dec.next_chunk().unwrap(), Some(Chunk(FourCC(*b"abc "), Box::new(*b"xyz"))));
+/// assert_eq!(dec.next_chunk().unwrap(), Some(Chunk(FourCC(*b"data"), Box::new(*b"de"))));
+///
+/// dec.push(b"JU");
+/// dec.finish();
+/// assert!(dec.next_chunk().is_err());
+/// ```
+///
+/// Such as PNG, whose length comes first and whose CRC follows the
+/// body:
+/// ```
+/// use iffc::{PushDecoder, Chunk, FourCC, PngLayout};
+///
+/// let mut dec = PushDecoder::new().with_layout(PngLayout);
+///
+/// dec.push(b"\x00\x00\x00\x03tEXtabc");
+/// assert_eq!(dec.next_chunk().unwrap(), None); // waits for the CRC.
+///
+/// dec.push(b"\x01\x02\x03\x04\x00\x00\x00\x00IEND\xAE\x42\x60\x82");
+/// assert_eq!(dec.next_chunk().unwrap(), Some(Chunk(FourCC(*b"tEXt"), Box::new(*b"abc"))));
+/// assert_eq!(dec.next_chunk().unwrap(), Some(Chunk::empty(*b"IEND")));
+///
+/// dec.push(b"\x00\x00\x00\x01IDATx\x00\x00");
+/// dec.finish();
+/// assert!(matches!(dec.next_chunk(), Err(iffc::DecodeError::UnexpectedEof { expected: 4, got: 2 })));
+/// ```
+pub struct PushDecoder<L: ChunkLayout = Layout> {
+    buf: Vec<u8>,
+    finished: bool,
+    consumed: u64,
+    layout: L,
+    max_chunk_size: u32
+}
+
+impl Default for PushDecoder
+{ fn default() -> Self { Self::new() } }
+
+impl PushDecoder {
+    /// Decodes little-endian (RIFF) chunks.
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            finished: false,
+            consumed: 0,
+            layout: Layout::RIFF,
+            max_chunk_size: crate::DEFAULT_MAX_CHUNK_SIZE
+        }
+    }
+
+    /// Decodes chunks whose size fields are stored in `endianness`.
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self
+    { self.layout.endianness = endianness; self }
+
+    /// Decodes chunks padded to `alignment`.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self
+    { self.layout.alignment = alignment; self }
+
+    /// Decodes chunks whose headers put their fields in `order`.
+    pub fn with_field_order(mut self, order: FieldOrder) -> Self
+    { self.layout.field_order = order; self }
+}
+
+impl<L: ChunkLayout> PushDecoder<L> {
+    /// Decodes chunks framed as described by `layout`.
+    pub fn with_layout<M: ChunkLayout>(self, layout: M) -> PushDecoder<M> {
+        PushDecoder {
+            buf: self.buf,
+            finished: self.finished,
+            consumed: self.consumed,
+            layout,
+            max_chunk_size: self.max_chunk_size
+        }
+    }
+
+    /// Rejects chunks declaring a body larger than `max` bytes, 64 MiB
+    /// by default, before any of it is buffered. Raised as far as
+    /// `u32::MAX`, a chunk whose framing can't be addressed on this
+    /// platform is a `DecodeError::SizeOverflow` instead:
+    /// ```
+    /// use iffc::{PushDecoder, DecodeError};
+    ///
+    /// let mut dec = PushDecoder::new().with_max_chunk_size(u32::MAX);
+    /// dec.push(b"data\xFF\xFF\xFF\xFF");
+    ///
+    /// match dec.next_chunk() {
+    ///     r if cfg!(target_pointer_width = "64") => assert_eq!(r.unwrap(), None),
+    ///     r => assert!(matches!(r, Err(DecodeError::SizeOverflow { size: u32::MAX, .. })))
+    /// }
+    /// ```
+    pub fn with_max_chunk_size(mut self, max: u32) -> Self
+    { self.max_chunk_size = max; self }
+
+    /// Appends the bytes which arrived from the stream.
+    pub fn push(&mut self, bytes: &[u8])
+    { self.buf.extend_from_slice(bytes) }
+
+    /// Marks the end of the stream, after which buffered bytes that
+    /// don't make up an entire chunk are reported as an error and the
+    /// trailing pad byte may be missing.
+    pub fn finish(&mut self) { self.finished = true }
+
+    /// Number of buffered bytes which are yet to be decoded.
+    pub fn buffered(&self) -> usize { self.buf.len() }
+
+    /// Takes the next entire chunk out of the buffer. `None` is
+    /// returned while more bytes are needed, or after `finish` once
+    /// the buffer has been drained.
+    pub fn next_chunk(&mut self) -> Result<Option<Chunk>, DecodeError> {
+        let (id, size) = match split_header(&self.buf, &self.layout) {
+            Some(header) => header,
+            None => return match self.buf.len() {
+                n if self.finished && n > 0 =>
+                    Err(DecodeError::UnexpectedEof { expected: 8, got: n }),
+                _ => Ok(None)
+            }
+        };
+
+        if size > self.max_chunk_size
+        { return Err(DecodeError::ChunkTooLarge { size, max: self.max_chunk_size }) }
+
+        let overflow = || DecodeError::SizeOverflow { id, size };
+
+        let body = body_len(size, &self.layout).and_then(|n| n.checked_add(8)).ok_or_else(overflow)?;
+        let end = body.checked_add(self.layout.alignment().padding(size as usize)).ok_or_else(overflow)?;
+
+        if self.buf.len() < end && !self.finished { return Ok(None) }
+
+        // finished, only the pad byte of the last chunk may be missing.
+        let (chunk, rest) = match split_chunk(&self.buf, &self.layout) {
+            Some(split) => split,
+            None => {
+                let got = self.buf.len() - 8;
+
+                return Err(match got < size as usize {
+                    true => DecodeError::TruncatedBody { id, offset: self.consumed, expected: size, got },
+                    false => DecodeError::UnexpectedEof {
+                        expected: layout::trailer_len(&self.layout),
+                        got: got - size as usize
+                    }
+                })
+            }
+        };
+
+        let data = chunk.1.into();
+        let end = self.buf.len() - rest.len();
+
+        self.buf.drain(..end);
+        self.consumed += end as u64;
+
+        Ok(Some(Chunk(id, data)))
+    }
+}
@@ -0,0 +1,214 @@
+//! RF64 is the extension of RIFF for files larger than 4 GiB, such
+//! files begin with `RF64` rather than `RIFF` and store `0xFFFFFFFF` as
+//! the size of the chunks which don't fit a `u32`. The actual 64-bit
+//! sizes of those are kept in a `ds64` chunk, which comes first.
+//!
+//! ```
+//! use std::io::Cursor;
+//! use iffc::FourCC;
+//! use iffc::rf64::{Rf64Decoder, Rf64Header};
+//!
+//! let inp = Cursor::new(&b"RF64\xFF\xFF\xFF\xFFWAVE\
+//!     ds64\x1C\x00\x00\x00\
+//!         \x2E\x00\x00\x00\x00\x00\x00\x00\
+//!         \x03\x00\x00\x00\x00\x00\x00\x00\
+//!         \x03\x00\x00\x00\x00\x00\x00\x00\
+//!         \x00\x00\x00\x00\
+//!     fmt \x02\x00\x00\x00ab\
+//!     data\xFF\xFF\xFF\xFFabc\0"[..]);
+//!
+//! let mut dec = Rf64Decoder::new(inp).unwrap();
+//! assert_eq!(dec.form_type(), FourCC(*b"WAVE"));
+//! assert_eq!(dec.ds64().map(|d| d.data_size), Some(3));
+//!
+//! let headers = dec.collect::<Result<Vec<_>, _>>().unwrap();
+//! assert_eq!(headers[1..], [
+//!     Rf64Header { id: FourCC(*b"fmt "), offset: 56, size: 2 },
+//!     Rf64Header { id: FourCC(*b"data"), offset: 66, size: 3 }
+//! ]);
+//! ```
+use std::io::{Read, Seek, SeekFrom};
+use crate::{FourCC, Decoder, DecodeError};
+
+/// Size field standing for a size which is kept in the `ds64` chunk.
+pub const SIZE_SENTINEL: u32 = 0xFFFFFFFF;
+
+/// The body of a `ds64` chunk, which carries the 64-bit sizes of the
+/// `RF64` container and of its `data` chunk, plus those of any other
+/// chunks in `table`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Ds64 {
+    pub riff_size: u64,
+    pub data_size: u64,
+    pub sample_count: u64,
+    pub table: Vec<(FourCC, u64)>
+}
+
+fn u64_at(b: &[u8], at: usize) -> u64 {
+    let mut n = [0u8; 8];
+    n.copy_from_slice(&b[at..at + 8]);
+    u64::from_le_bytes(n)
+}
+
+impl Ds64 {
+    /// Parses the body of a `ds64` chunk, a body too short for the
+    /// fixed fields or for the entries of its table is reported as a
+    /// `DecodeError::TruncatedBody`, at offset 12 where the `ds64` chunk
+    /// must be.
+    pub fn parse(body: &[u8]) -> Result<Self, DecodeError> {
+        let truncated = |expected: usize| DecodeError::TruncatedBody {
+            id: FourCC(*b"ds64"),
+            offset: 12,
+            expected: expected as u32,
+            got: body.len()
+        };
+
+        if body.len() < 28 { return Err(truncated(28)) }
+
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&body[24..28]);
+
+        let len = u32::from_le_bytes(len) as usize;
+        let expected = len.checked_mul(12).and_then(|n| n.checked_add(28))
+            .filter(|&n| n <= body.len())
+            .ok_or_else(|| truncated(28 + len.saturating_mul(12)))?;
+
+        let table = body[28..expected].chunks(12).map(|e| {
+            let mut id = [0u8; 4];
+            id.copy_from_slice(&e[..4]);
+            (FourCC(id), u64_at(e, 4))
+        }).collect();
+
+        Ok(Self {
+            riff_size: u64_at(body, 0),
+            data_size: u64_at(body, 8),
+            sample_count: u64_at(body, 16),
+            table
+        })
+    }
+
+    /// Actual size of a chunk with `id` whose header declared `size`,
+    /// which is `size` itself unless it is the `SIZE_SENTINEL`.
+    pub fn size_of(&self, id: FourCC, size: u32) -> u64 {
+        if size != SIZE_SENTINEL { return size as u64 }
+
+        match &id.0 {
+            b"RF64" | b"RIFF" => self.riff_size,
+            b"data" => self.data_size,
+            _ => self.table.iter()
+                .find(|(i, _)| *i == id)
+                .map_or(size as u64, |&(_, s)| s)
+        }
+    }
+}
+
+/// Location of a chunk within an RF64 file, see `ChunkHeader`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Rf64Header {
+    pub id: FourCC,
+    pub offset: u64,
+    pub size: u64
+}
+
+/// Iterates over the headers of the chunks in an `RF64` (or plain
+/// `RIFF`) file, seeking past their bodies. Sizes stored as the
+/// `SIZE_SENTINEL` are resolved through the `ds64` chunk, which is
+/// read if it is the first chunk of the file. A size which would put
+/// the end of the chunk past `u64::MAX` is a `DecodeError::SizeOverflow`.
+/// ```
+/// use std::io::Cursor;
+/// use iffc::DecodeError;
+/// use iffc::rf64::Rf64Decoder;
+///
+/// let inp = Cursor::new(&b"RF64\xFF\xFF\xFF\xFFWAVE\
+///     ds64\x1C\x00\x00\x00\
+///         \x00\x00\x00\x00\x00\x00\x00\x00\
+///         \xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\
+///         \x00\x00\x00\x00\x00\x00\x00\x00\
+///         \x00\x00\x00\x00\
+///     data\xFF\xFF\xFF\xFF"[..]);
+///
+/// let mut dec = Rf64Decoder::new(inp).unwrap();
+/// assert!(dec.next().unwrap().is_ok());
+/// assert!(matches!(dec.next(), Some(Err(DecodeError::SizeOverflow { size: 0xFFFFFFFF, .. }))));
+///
+/// // a ds64 chunk declaring 4 GiB is only read as far as its table.
+/// let inp = Cursor::new(&b"RF64\xFF\xFF\xFF\xFFWAVEds64\xFE\xFF\xFF\xFF"[..]);
+/// assert!(matches!(Rf64Decoder::new(inp), Err(DecodeError::TruncatedBody { expected: 28, got: 0, .. })));
+/// ```
+pub struct Rf64Decoder<R: Read + Seek> {
+    dec: Decoder<R>,
+    form_type: FourCC,
+    ds64: Option<Ds64>,
+    pos: u64
+}
+
+impl<R: Read + Seek> Rf64Decoder<R> {
+    /// Reads the `RF64` header of `r` along with its `ds64` chunk.
+    pub fn new(r: R) -> Result<Self, DecodeError> {
+        let mut dec = Decoder::new(r).with_max_chunk_size(u32::MAX);
+
+        if dec.read_header()?.is_none()
+        { return Err(DecodeError::UnexpectedEof { expected: 8, got: 0 }) }
+
+        let mut form_type = [0u8; 4];
+        dec.get_mut().read_exact(&mut form_type)?;
+
+        let pos = dec.get_mut().stream_position()?;
+        let mut this = Self { dec, form_type: FourCC(form_type), ds64: None, pos };
+
+        if let Some((id, size)) = this.dec.read_header()? {
+            if id == "ds64" && size != SIZE_SENTINEL {
+                // only the fixed fields and the table they announce are
+                // read, and buffered as they arrive, whatever the size.
+                let r = this.dec.get_mut();
+                let mut body = Vec::new();
+                r.take((size as u64).min(28)).read_to_end(&mut body)?;
+
+                if body.len() == 28 {
+                    let len = u32::from_le_bytes([body[24], body[25], body[26], body[27]]) as u64;
+                    r.take((size as u64 - 28).min(len * 12)).read_to_end(&mut body)?;
+                }
+
+                this.ds64 = Some(Ds64::parse(&body)?);
+            }
+        }
+
+        // the ds64 chunk itself is yielded by the iterator as well.
+        this.dec.get_mut().seek(SeekFrom::Start(pos))?;
+
+        Ok(this)
+    }
+
+    /// Form type of the file, such as `WAVE`.
+    pub fn form_type(&self) -> FourCC { self.form_type }
+
+    /// The `ds64` chunk of the file, `None` if it had none.
+    pub fn ds64(&self) -> Option<&Ds64> { self.ds64.as_ref() }
+
+    /// Unwraps the underlying reader.
+    pub fn into_inner(self) -> R { self.dec.into_inner() }
+
+    fn read_header(&mut self) -> Result<Option<Rf64Header>, DecodeError> {
+        let (id, declared) = match self.dec.read_header()? {
+            Some(h) => h,
+            None => return Ok(None)
+        };
+
+        let size = self.ds64.as_ref().map_or(declared as u64, |d| d.size_of(id, declared));
+        let overflow = || DecodeError::SizeOverflow { id, size: declared };
+        let offset = self.pos.checked_add(8).ok_or_else(overflow)?;
+
+        self.pos = offset.checked_add(size).and_then(|n| n.checked_add(size % 2)).ok_or_else(overflow)?;
+        self.dec.get_mut().seek(SeekFrom::Start(self.pos))?;
+
+        Ok(Some(Rf64Header { id, offset, size }))
+    }
+}
+
+impl<R: Read + Seek> Iterator for Rf64Decoder<R> {
+    type Item = Result<Rf64Header, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item>
+    { self.read_header().transpose() }
+}
@@ -0,0 +1,46 @@
+//! Helpers for the chunks found in Microsoft RIFF files.
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::borrow::ToOwned;
+use crate::{Chunk, FourCC, SliceDecoder};
+
+/// Collects the metadata of the `LIST` chunks of form type `INFO`
+/// among `chunks`, such as the children of a `RIFF` group. Each of
+/// their sub-chunks (`INAM`, `IART`, `ICMT`...) holds a NUL-terminated
+/// string, which is returned with the NULs trimmed and converted
+/// lossily if it isn't UTF-8. A malformed sub-chunk ends the list it
+/// appears in, the entries before it are kept.
+///
+/// ```
+/// use iffc::{Decoder, Group, Chunk, FourCC, Endianness, Alignment};
+/// use iffc::riff::read_info;
+///
+/// let inp = b"RIFF\x34\x00\x00\x00WAVE\
+///             LIST\x28\x00\x00\x00INFO\
+///             INAM\x06\x00\x00\x00Title\0\
+///             IART\x03\x00\x00\x00Me\0\0\
+///             ICMT\x02\x00\x00\x00\xFFx";
+///
+/// let riff = Decoder::new(&inp[..]).next().unwrap().unwrap();
+/// let wave = Group::decode(&riff, Endianness::Little, Alignment::Two).unwrap();
+/// let info = read_info(&wave.children);
+///
+/// assert_eq!(info[&FourCC(*b"INAM")], "Title");
+/// assert_eq!(info[&FourCC(*b"IART")], "Me");
+/// assert_eq!(info[&FourCC(*b"ICMT")], "\u{FFFD}x");
+/// ```
+pub fn read_info(chunks: &[Chunk]) -> BTreeMap<FourCC, String> {
+    let mut info = BTreeMap::new();
+
+    let lists = chunks.iter()
+        .filter(|c| c.0 == "LIST" && c.1.starts_with(b"INFO"));
+
+    for list in lists {
+        for entry in SliceDecoder::new(&list.1[4..]).map_while(Result::ok) {
+            let text = String::from_utf8_lossy(entry.1);
+            info.insert(entry.0, text.trim_end_matches('\0').to_owned());
+        }
+    }
+
+    info
+}
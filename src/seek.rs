@@ -0,0 +1,119 @@
+use std::io::{Read, Seek, SeekFrom};
+use crate::{FourCC, Decoder, DecodeError, ChunkLayout, Layout};
+
+/// Location of a chunk within a stream, as yielded by a `SeekDecoder`.
+/// `offset` is where its body begins and `size` how long it is.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ChunkHeader {
+    pub id: FourCC,
+    pub offset: u64,
+    pub size: u32
+}
+
+/// Iterates over the headers of the chunks of a seekable stream, only
+/// the 8-byte header of each chunk is read and its body (and the pad
+/// byte) is seeked past. This makes indexing a large file a matter of
+/// its number of chunks, rather than of its size.
+///
+/// Since bodies aren't read, a stream which ends midway the body of
+/// its last chunk is not reported as truncated.
+///
+/// ```
+/// use std::io::{Cursor, Read, Seek, SeekFrom};
+/// use iffc::{SeekDecoder, ChunkHeader, FourCC};
+///
+/// let inp = Cursor::new(b"fmt \x03\x00\x00\x00abc\0data\x02\x00\x00\x00de");
+/// let mut dec = SeekDecoder::new(inp);
+///
+/// let index = dec.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(index, [
+///     ChunkHeader { id: FourCC(*b"fmt "), offset: 8, size: 3 },
+///     ChunkHeader { id: FourCC(*b"data"), offset: 20, size: 2 }
+/// ]);
+///
+/// let mut inp = dec.into_inner();
+/// let mut data = [0u8; 2];
+///
+/// inp.seek(SeekFrom::Start(index[1].offset)).unwrap();
+/// inp.read_exact(&mut data).unwrap();
+/// assert_eq!(&data, b"de");
+/// ```
+pub struct SeekDecoder<R: Read + Seek, L: ChunkLayout = Layout>(Decoder<R, L>);
+
+impl<R: Read + Seek> SeekDecoder<R> {
+    /// Reads little-endian (RIFF) chunk headers from `r`.
+    pub fn new(r: R) -> Self { Self(Decoder::new(r)) }
+}
+
+impl<R: Read + Seek, L: ChunkLayout> SeekDecoder<R, L> {
+    /// Unwraps the underlying reader.
+    pub fn into_inner(self) -> R { self.0.into_inner() }
+
+    /// Counts the chunks which are left to be yielded by walking over
+    /// their headers, then seeks back to where it started and restores
+    /// the state of the decoder, chunk count included, so that the
+    /// iteration carries on unaffected. The reader is restored even if
+    /// a malformed header stops the count.
+    /// ```
+    /// use std::io::Cursor;
+    /// use iffc::{SeekDecoder, Decoder};
+    ///
+    /// let inp = Cursor::new(b"fmt \x03\x00\x00\x00abc\0\
+    ///                         LIST\x04\x00\x00\x00INFO\
+    ///                         data\x02\x00\x00\x00de");
+    /// let mut dec = SeekDecoder::new(inp);
+    ///
+    /// dec.next();
+    /// assert_eq!(dec.count_remaining().unwrap(), 2);
+    /// assert_eq!(dec.count_remaining().unwrap(), dec.count());
+    ///
+    /// // the headers walked over don't count against `with_max_chunks`.
+    /// let inp = Cursor::new(b"fmt \x02\x00\x00\x00abdata\x00\x00\x00\x00");
+    /// let mut dec = SeekDecoder::from(Decoder::new(inp).with_max_chunks(2));
+    ///
+    /// assert_eq!(dec.count_remaining().unwrap(), 2);
+    /// assert_eq!(dec.filter(|h| h.is_ok()).count(), 2);
+    /// ```
+    pub fn count_remaining(&mut self) -> Result<usize, DecodeError> {
+        let start = self.0.get_mut().stream_position()?;
+        let state = self.0.snapshot();
+
+        let mut n = 0;
+        let count = loop {
+            match self.read_header() {
+                Ok(Some(_)) => n += 1,
+                Ok(None) => break Ok(n),
+                Err(e) => break Err(e)
+            }
+        };
+
+        self.0.get_mut().seek(SeekFrom::Start(start))?;
+        self.0.restore(state);
+
+        count
+    }
+
+    fn read_header(&mut self) -> Result<Option<ChunkHeader>, DecodeError> {
+        let (id, size) = match self.0.read_header()? {
+            Some(h) => h,
+            None => return Ok(None)
+        };
+
+        let skip = size as u64 + self.0.tail_len(size as usize) as u64;
+        let end = self.0.get_mut().seek(SeekFrom::Current(skip as i64))?;
+
+        Ok(Some(ChunkHeader { id, offset: end - skip, size }))
+    }
+}
+
+/// Reads the chunk headers with the layout which `decoder` is
+/// configured with.
+impl<R: Read + Seek, L: ChunkLayout> From<Decoder<R, L>> for SeekDecoder<R, L>
+{ fn from(decoder: Decoder<R, L>) -> Self { Self(decoder) } }
+
+impl<R: Read + Seek, L: ChunkLayout> Iterator for SeekDecoder<R, L> {
+    type Item = Result<ChunkHeader, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item>
+    { self.read_header().transpose() }
+}
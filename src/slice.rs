@@ -0,0 +1,188 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ops::Range;
+use crate::{Chunk, FourCC, DecodeError, ChunkLayout, Layout, RiffLayout};
+use crate::layout;
+
+/// A chunk whose body is borrowed from the buffer it was parsed out
+/// of, rather than copied into an allocation of its own.
+///
+/// `0` — four-byte identity of chunk.
+/// `1` — byte-data encapsulated inside it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct BorrowedChunk<'a>(pub FourCC, pub &'a [u8]);
+
+impl From<BorrowedChunk<'_>> for Chunk
+{ fn from(c: BorrowedChunk) -> Self { Chunk(c.0, c.1.into()) } }
+
+impl<'a> From<&'a Chunk> for BorrowedChunk<'a>
+{ fn from(c: &'a Chunk) -> Self { BorrowedChunk(c.0, &c.1) } }
+
+impl BorrowedChunk<'_> {
+    /// Where the body lies within `buf`, the buffer it was parsed out
+    /// of, `None` if it isn't part of it. A buffer shared by reference
+    /// counting can hand out the body by this range without copying.
+    /// ```
+    /// use std::rc::Rc;
+    /// use iffc::SliceDecoder;
+    ///
+    /// let buf: Rc<[u8]> = Rc::from(&b"fmt \x03\x00\x00\x00abc\0data\x02\x00\x00\x00de"[..]);
+    /// let ranges = SliceDecoder::new(&buf)
+    ///     .map(|c| c.unwrap().range_in(&buf).unwrap())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(ranges, [8..11, 20..22]);
+    /// assert_eq!(&buf[ranges[1].clone()], b"de");
+    ///
+    /// assert_eq!(SliceDecoder::new(&buf).next().unwrap().unwrap().range_in(b"elsewhere"), None);
+    /// ```
+    pub fn range_in(&self, buf: &[u8]) -> Option<Range<usize>> {
+        let start = (self.1.as_ptr() as usize).checked_sub(buf.as_ptr() as usize)?;
+        let end = start + self.1.len();
+
+        if end <= buf.len() { Some(start..end) } else { None }
+    }
+}
+
+/// Reads the header at the start of `input`, if it holds one.
+pub(crate) fn split_header<L: ChunkLayout>(input: &[u8], layout: &L) -> Option<(FourCC, u32)> {
+    let mut header = [0u8; 8];
+    header.copy_from_slice(input.get(..8)?);
+
+    Some(layout::parse_header(layout, &header))
+}
+
+/// Number of bytes a body of `size` bytes and its checksum take, `None`
+/// if that doesn't fit a `usize`.
+pub(crate) fn body_len<L: ChunkLayout>(size: u32, layout: &L) -> Option<usize>
+{ usize::try_from(size).ok()?.checked_add(layout::trailer_len(layout)) }
+
+/// Splits the first chunk off `input`, `None` if `input` doesn't hold
+/// an entire chunk. The pad byte of a chunk at the very end of `input`
+/// may be missing, as with the `Decoder`. Checksums are skipped over
+/// without being verified.
+pub(crate) fn split_chunk<'a, L: ChunkLayout>(input: &'a [u8], layout: &L)
+    -> Option<(BorrowedChunk<'a>, &'a [u8])>
+{
+    let (id, size) = split_header(input, layout)?;
+    let rest = &input[8..];
+
+    if rest.len() < body_len(size, layout)? { return None }
+
+    let size = size as usize;
+    let (body, rest) = rest.split_at(size);
+    let rest = &rest[layout::trailer_len(layout)..];
+    let pad = layout.alignment().padding(size).min(rest.len());
+
+    Some((BorrowedChunk(id, body), &rest[pad..]))
+}
+
+/// Parses all the RIFF (little-endian, even-aligned) chunks held in
+/// `input` without copying their bodies, also returns the bytes left
+/// over after the last entire chunk, if any.
+///
+/// ```
+/// use iffc::{parse_slice, BorrowedChunk, FourCC};
+///
+/// let inp = b"abc \x03\x00\x00\x00xyz\0data\x02\x00\x00\x00deJU";
+/// let (chunks, rest) = parse_slice(inp).unwrap();
+///
+/// assert_eq!(chunks, [
+///     BorrowedChunk(FourCC(*b"abc "), b"xyz"),
+///     BorrowedChunk(FourCC(*b"data"), b"de")
+/// ]);
+/// assert_eq!(rest, b"JU");
+/// ```
+pub fn parse_slice(mut input: &[u8]) -> Result<(Vec<BorrowedChunk<'_>>, &[u8]), DecodeError> {
+    let mut chunks = Vec::new();
+
+    while let Some((chunk, rest)) = split_chunk(input, &RiffLayout) {
+        chunks.push(chunk);
+        input = rest;
+    }
+
+    Ok((chunks, input))
+}
+
+/// Iterates over the chunks of a buffer without copying their bodies,
+/// like `parse_slice`, but can be rewound with `reset` to parse the
+/// same bytes again. A buffer ending inside of a chunk yields a single
+/// error, after which the iteration ends.
+///
+/// ```
+/// use iffc::{SliceDecoder, BorrowedChunk, FourCC, DecodeError};
+///
+/// let inp = b"abc \x03\x00\x00\x00xyz\0data\x02\x00\x00\x00de";
+/// let mut dec = SliceDecoder::new(inp);
+///
+/// assert_eq!(dec.by_ref().count(), 2);
+/// dec.reset();
+///
+/// let data = dec.by_ref().map(Result::unwrap).find(|c| c.0 == "data");
+/// assert_eq!(data, Some(BorrowedChunk(FourCC(*b"data"), b"de")));
+///
+/// let mut dec = SliceDecoder::new(&inp[..14]);
+/// assert!(dec.next().unwrap().is_ok());
+/// assert!(matches!(dec.next(), Some(Err(DecodeError::UnexpectedEof { got: 2, .. }))));
+/// assert!(dec.next().is_none());
+/// assert_eq!(dec.rest(), b"da");
+/// ```
+///
+/// Any borrowed buffer will do, such as a memory-mapped file, the
+/// chunks yielded being tied to the lifetime of the mapping so that
+/// they can't outlive it.
+pub struct SliceDecoder<'a, L: ChunkLayout = Layout> {
+    input: &'a [u8],
+    rest: &'a [u8],
+    layout: L,
+    failed: bool
+}
+
+impl<'a> SliceDecoder<'a> {
+    /// Decodes little-endian (RIFF) chunks from `input`.
+    pub fn new(input: &'a [u8]) -> Self
+    { Self { input, rest: input, layout: Layout::RIFF, failed: false } }
+}
+
+impl<'a, L: ChunkLayout> SliceDecoder<'a, L> {
+    /// Decodes chunks framed as described by `layout`.
+    pub fn with_layout<M: ChunkLayout>(self, layout: M) -> SliceDecoder<'a, M>
+    { SliceDecoder { input: self.input, rest: self.rest, layout, failed: self.failed } }
+
+    /// Rewinds to the start of the buffer.
+    pub fn reset(&mut self)
+    { self.rest = self.input; self.failed = false }
+
+    /// Bytes which haven't been parsed yet.
+    pub fn rest(&self) -> &'a [u8] { self.rest }
+
+    /// Offset of the next chunk within the buffer.
+    pub fn offset(&self) -> usize { self.input.len() - self.rest.len() }
+}
+
+impl<'a, L: ChunkLayout> Iterator for SliceDecoder<'a, L> {
+    type Item = Result<BorrowedChunk<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.rest.is_empty() { return None }
+
+        if let Some((chunk, rest)) = split_chunk(self.rest, &self.layout) {
+            self.rest = rest;
+            return Some(Ok(chunk))
+        }
+
+        self.failed = true;
+
+        Some(Err(match split_header(self.rest, &self.layout) {
+            Some((id, size)) if body_len(size, &self.layout).is_none() =>
+                DecodeError::SizeOverflow { id, size },
+            Some((id, size)) => DecodeError::TruncatedBody {
+                id,
+                offset: self.offset() as u64,
+                expected: size,
+                got: self.rest.len() - 8
+            },
+            None => DecodeError::UnexpectedEof { expected: 8, got: self.rest.len() }
+        }))
+    }
+}
@@ -0,0 +1,235 @@
+use std::io::{self, Read, ErrorKind};
+use std::convert::TryFrom;
+use std::ops::Range;
+use crate::{FourCC, BorrowedChunk, DecodeError, ChunkLayout, Layout, Endianness, Crc32, DEFAULT_MAX_CHUNK_SIZE};
+use crate::layout::{parse_header, trailer_len};
+
+/// Decodes the chunks of a reader into a buffer of its own which is
+/// reused from one chunk to the next, handing out bodies borrowed from
+/// it rather than allocating each of them, which suits sockets and
+/// pipes carrying many small chunks. Being borrowed, a body is only
+/// valid until `next` is called again, so this isn't an `Iterator`.
+///
+/// The buffer refills with as much as the reader hands out at a time.
+/// When a chunk doesn't fit what's left of it, the bytes not consumed
+/// yet are moved to its start, and it's only grown if the chunk is
+/// larger than the whole buffer. Even then it doubles at most once the
+/// bytes already read fill it, so that a size declared by a peer
+/// doesn't allocate more than twice what it actually sent.
+/// ```
+/// use std::io::{self, Read};
+/// use iffc::{StreamingDecoder, Decoder, Encoder, Chunk, FourCC};
+///
+/// // a socket handing out 7 bytes at a time.
+/// struct Socket<'a>(&'a [u8]);
+///
+/// impl Read for Socket<'_> {
+///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+///         let n = buf.len().min(7);
+///         self.0.read(&mut buf[..n])
+///     }
+/// }
+///
+/// let mut enc = Encoder::new(Vec::new());
+/// for n in 0..1000u32 {
+///     enc.write_chunk(&Chunk(FourCC(*b"pkt "), vec![n as u8; n as usize % 5].into()))?;
+/// }
+/// let out = enc.into_inner();
+///
+/// let mut dec = StreamingDecoder::new(Socket(&out));
+/// let mut n = 0u32;
+///
+/// while let Some(chunk) = dec.next() {
+///     assert_eq!(chunk?.1, &vec![n as u8; n as usize % 5][..]);
+///     n += 1;
+/// }
+///
+/// assert_eq!(n, 1000);
+/// assert_eq!(dec.buffer_len(), 8192);
+///
+/// // the same chunks as an allocating decoder reads.
+/// let owned = Decoder::new(Socket(&out)).collect::<Result<Vec<Chunk>, _>>()?;
+/// let mut dec = StreamingDecoder::new(Socket(&out));
+/// for chunk in &owned {
+///     assert_eq!(Chunk::from(dec.next().unwrap()?), *chunk);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// The errors are those of a `Decoder`, after which no more chunks are
+/// read:
+/// ```
+/// use iffc::{StreamingDecoder, DecodeError};
+///
+/// let mut dec = StreamingDecoder::new(&b"data\x04\x00\x00\x00ab"[..]);
+/// assert!(matches!(dec.next(), Some(Err(DecodeError::TruncatedBody { expected: 4, got: 2, .. }))));
+/// assert!(dec.next().is_none());
+///
+/// // 64 MiB declared, of which 10 bytes arrive.
+/// let mut inp = b"data\x00\x00\x00\x04".to_vec();
+/// inp.extend_from_slice(&[0; 10]);
+///
+/// let mut dec = StreamingDecoder::new(&inp[..]);
+/// assert!(matches!(dec.next(), Some(Err(DecodeError::TruncatedBody { got: 10, .. }))));
+/// assert_eq!(dec.buffer_len(), 8192);
+///
+/// let mut dec = StreamingDecoder::new(&b"data\x04\x00\x00\x00abcddat"[..]);
+/// assert_eq!(dec.next().unwrap().unwrap().1, b"abcd");
+/// assert!(matches!(dec.next(), Some(Err(DecodeError::UnexpectedEof { expected: 8, got: 3 }))));
+/// ```
+pub struct StreamingDecoder<R: Read, L: ChunkLayout = Layout> {
+    r: R,
+    layout: L,
+    buf: Vec<u8>,
+    start: usize,
+    end: usize,
+    max_chunk_size: u32,
+    pos: u64,
+    done: bool
+}
+
+/// Size the buffer starts with.
+const INITIAL_LEN: usize = 8192;
+
+impl<R: Read> StreamingDecoder<R> {
+    /// Decodes little-endian (RIFF) chunks from `r`.
+    pub fn new(r: R) -> Self {
+        Self {
+            r,
+            layout: Layout::RIFF,
+            buf: Vec::new(),
+            start: 0,
+            end: 0,
+            max_chunk_size: DEFAULT_MAX_CHUNK_SIZE,
+            pos: 0,
+            done: false
+        }
+    }
+}
+
+impl<R: Read, L: ChunkLayout> StreamingDecoder<R, L> {
+    /// Decodes chunks framed as described by `layout`.
+    pub fn with_layout<M: ChunkLayout>(self, layout: M) -> StreamingDecoder<R, M> {
+        StreamingDecoder {
+            r: self.r,
+            layout,
+            buf: self.buf,
+            start: self.start,
+            end: self.end,
+            max_chunk_size: self.max_chunk_size,
+            pos: self.pos,
+            done: self.done
+        }
+    }
+
+    /// Same as `Decoder::with_max_chunk_size`, which also bounds how
+    /// large the buffer grows.
+    pub fn with_max_chunk_size(mut self, max: u32) -> Self
+    { self.max_chunk_size = max; self }
+
+    /// Number of bytes the buffer holds room for.
+    pub fn buffer_len(&self) -> usize { self.buf.len() }
+
+    /// Number of bytes consumed so far, as `Decoder::offset`.
+    pub fn offset(&self) -> u64 { self.pos }
+
+    /// Reads the next chunk, whose body stays borrowed from the buffer
+    /// until this is called again.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<BorrowedChunk<'_>, DecodeError>> {
+        if self.done { return None }
+
+        match self.read_chunk() {
+            Ok(Some((id, body))) => Some(Ok(BorrowedChunk(id, &self.buf[body]))),
+            Ok(None) => { self.done = true; None },
+            Err(e) => { self.done = true; Some(Err(e)) }
+        }
+    }
+
+    /// Makes sure `n` bytes past `start` are buffered, reading what's
+    /// missing. `false` if the stream ended before.
+    fn fill(&mut self, n: usize) -> io::Result<bool> {
+        if self.end - self.start >= n { return Ok(true) }
+
+        self.buf.copy_within(self.start..self.end, 0);
+        self.end -= self.start;
+        self.start = 0;
+
+        while self.end < n {
+            if self.end == self.buf.len() {
+                let len = self.buf.len().saturating_mul(2).clamp(INITIAL_LEN, n.max(INITIAL_LEN));
+                self.buf.resize(len, 0);
+            }
+
+            match self.r.read(&mut self.buf[self.end..]) {
+                Ok(0) => return Ok(false),
+                Ok(read) => self.end += read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {},
+                Err(e) => return Err(e)
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Reads the next chunk into the buffer, returning its id and where
+    /// its body lies.
+    fn read_chunk(&mut self) -> Result<Option<(FourCC, Range<usize>)>, DecodeError> {
+        if !self.fill(8)? {
+            return match self.end - self.start {
+                0 => Ok(None),
+                got => Err(DecodeError::UnexpectedEof { expected: 8, got })
+            }
+        }
+
+        let mut header = [0u8; 8];
+        header.copy_from_slice(&self.buf[self.start..self.start + 8]);
+        let (id, size) = parse_header(&self.layout, &header);
+
+        if size > self.max_chunk_size
+        { return Err(DecodeError::ChunkTooLarge { size, max: self.max_chunk_size }) }
+
+        let trailer = trailer_len(&self.layout);
+        let len = usize::try_from(size).ok()
+            .and_then(|n| n.checked_add(8 + trailer))
+            .ok_or(DecodeError::SizeOverflow { id, size })?;
+
+        if !self.fill(len)? {
+            let got = self.end - self.start - 8;
+
+            return Err(match got < size as usize {
+                true => DecodeError::TruncatedBody { id, offset: self.pos, expected: size, got },
+                false => DecodeError::UnexpectedEof { expected: trailer, got: got - size as usize }
+            })
+        }
+
+        let body = self.start + 8..self.start + 8 + size as usize;
+
+        if trailer != 0 {
+            let mut expected = Crc32::new();
+            expected.update(&id.0);
+            expected.update(&self.buf[body.clone()]);
+
+            let mut crc = [0u8; 4];
+            crc.copy_from_slice(&self.buf[body.end..body.end + 4]);
+
+            let expected = expected.finish();
+            let found = Endianness::Big.read_u32(crc);
+
+            if expected != found
+            { return Err(DecodeError::BadChecksum { expected, found }) }
+        }
+
+        // the pad byte of the very last chunk may be missing, as with
+        // a `Decoder`.
+        let overflow = || DecodeError::SizeOverflow { id, size };
+        let padded = len.checked_add(self.layout.alignment().padding(size as usize)).ok_or_else(overflow)?;
+        self.fill(padded)?;
+
+        let consumed = padded.min(self.end - self.start);
+        self.pos = self.pos.checked_add(consumed as u64).ok_or_else(overflow)?;
+        self.start += consumed;
+
+        Ok(Some((id, body)))
+    }
+}
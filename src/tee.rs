@@ -0,0 +1,57 @@
+use std::io::{self, Read, Write};
+use crate::{Decoder, Layout};
+
+/// Reader which writes every byte read from `R` into `W` as well, see
+/// `TeeDecoder`.
+#[derive(Debug)]
+pub struct Tee<R: Read, W: Write> {
+    r: R,
+    w: W
+}
+
+impl<R: Read, W: Write> Tee<R, W> {
+    pub fn new(r: R, w: W) -> Self { Self { r, w } }
+
+    /// Reference to the writer, holding what was read so far.
+    pub fn get_ref(&self) -> &W { &self.w }
+
+    /// Unwraps the reader and the writer.
+    pub fn into_inner(self) -> (R, W) { (self.r, self.w) }
+}
+
+impl<R: Read, W: Write> Read for Tee<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.r.read(buf)?;
+        self.w.write_all(&buf[..n])?;
+        Ok(n)
+    }
+}
+
+/// A decoder copying the bytes it consumes into a writer, headers, pad
+/// bytes and bodies skipped over included, so that a stream can be
+/// archived while it's parsed. Since a `Decoder` never reads past what
+/// it needs, the writer holds exactly the bytes of the chunks decoded
+/// so far. A failure to write is yielded as a `DecodeError::Io`.
+/// ```
+/// use iffc::{TeeDecoder, Chunk, FourCC};
+///
+/// let inp = b"fmt \x03\x00\x00\x00abc\0JUNK\x01\x00\x00\x00x\0data\x02\x00\x00\x00de";
+///
+/// let mut dec = TeeDecoder::tee(&inp[..], Vec::new()).with_skip_filler(true);
+/// assert_eq!(dec.next().unwrap()?, Chunk(FourCC(*b"fmt "), Box::new(*b"abc")));
+/// assert_eq!(dec.get_mut().get_ref(), &inp[..12]);
+///
+/// assert_eq!(dec.next().unwrap()?.0, FourCC(*b"data"));
+/// assert!(dec.next().is_none());
+///
+/// let (_, copy) = dec.into_inner().into_inner();
+/// assert_eq!(copy, &inp[..]);
+/// # Ok::<(), iffc::DecodeError>(())
+/// ```
+pub type TeeDecoder<R, W, L = Layout> = Decoder<Tee<R, W>, L>;
+
+impl<R: Read, W: Write> Decoder<Tee<R, W>> {
+    /// Decodes the chunks of `r`, copying what's read into `w`.
+    pub fn tee(r: R, w: W) -> Self
+    { Decoder::new(Tee::new(r, w)) }
+}
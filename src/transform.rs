@@ -0,0 +1,39 @@
+use std::io::{Read, Write};
+use crate::{Chunk, Decoder, Encoder, DecodeError};
+
+/// Decodes each RIFF chunk of `reader`, passes it through `f` and
+/// encodes what it returns into `writer`, dropping the chunk if it
+/// returns `None`. The chunks are written as they're read, so a
+/// failure on either side leaves `writer` with the chunks before it.
+/// The writer is flushed once the reader runs out of chunks.
+/// ```
+/// use iffc::{transform, Decoder, Chunk, FourCC};
+///
+/// let inp = b"fmt \x02\x00\x00\x00ab\
+///             JUNK\x03\x00\x00\x00xyz\0\
+///             data\x01\x00\x00\x00c\0\
+///             JUNK\x00\x00\x00\x00";
+///
+/// let mut out = Vec::new();
+/// transform(&inp[..], &mut out, |c| Some(c).filter(|c| c.0 != "JUNK"))?;
+///
+/// assert_eq!(Decoder::new(&out[..]).collect::<Result<Vec<Chunk>, _>>()?, [
+///     Chunk(FourCC(*b"fmt "), Box::new(*b"ab")),
+///     Chunk(FourCC(*b"data"), Box::new(*b"c"))
+/// ]);
+/// assert_eq!(out.len(), 10 + 10);
+/// # Ok::<(), iffc::DecodeError>(())
+/// ```
+pub fn transform<R, W, F>(reader: R, writer: W, mut f: F) -> Result<(), DecodeError>
+    where R: Read, W: Write, F: FnMut(Chunk) -> Option<Chunk>
+{
+    let mut enc = Encoder::new(writer);
+
+    for chunk in Decoder::new(reader) {
+        if let Some(chunk) = f(chunk?)
+        { enc.write_chunk(&chunk)?; }
+    }
+
+    enc.finish()?;
+    Ok(())
+}
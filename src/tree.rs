@@ -0,0 +1,206 @@
+use std::io::Read;
+use crate::{Chunk, FourCC, Decoder, DecodeError, Group, ChunkLayout, Layout};
+
+/// A node of the tree of chunks built by a `TreeDecoder`, either an
+/// ordinary chunk or a container with its nested nodes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Node {
+    Leaf(Chunk),
+    Branch { id: FourCC, form_type: FourCC, children: Vec<Node> }
+}
+
+/// Decodes every chunk of a `Decoder` into a tree of `Node`s, where
+/// each container chunk is descended into and its sub-chunks become
+/// the children of a `Node::Branch`. The size fields and padding of
+/// nested chunks are read the same way as the outermost ones.
+///
+/// Which chunks are containers is decided by a predicate, the ids
+/// known to `Group::is_container` by default. The nesting is limited
+/// to `max_depth` levels (64 by default), deeper containers fail with
+/// a `DecodeError::DepthExceeded` to keep hostile files from running
+/// out of stack.
+///
+/// ```
+/// use iffc::{Decoder, TreeDecoder, Node, Chunk, FourCC, DecodeError};
+///
+/// let inp = b"RIFF\x26\x00\x00\x00WAVE\
+///             LIST\x1A\x00\x00\x00adtl\
+///             LIST\x0E\x00\x00\x00abcd\
+///             note\x02\x00\x00\x00hi";
+///
+/// let tree = Decoder::new(&inp[..]).into_tree().unwrap();
+///
+/// assert_eq!(tree, [
+///     Node::Branch { id: FourCC(*b"RIFF"), form_type: FourCC(*b"WAVE"), children: vec![
+///         Node::Branch { id: FourCC(*b"LIST"), form_type: FourCC(*b"adtl"), children: vec![
+///             Node::Branch { id: FourCC(*b"LIST"), form_type: FourCC(*b"abcd"), children: vec![
+///                 Node::Leaf(Chunk(FourCC(*b"note"), Box::new(*b"hi")))
+///             ]}
+///         ]}
+///     ]}
+/// ]);
+///
+/// let shallow = TreeDecoder::new(Decoder::new(&inp[..]))
+///     .with_max_depth(2)
+///     .decode();
+///
+/// assert!(matches!(shallow, Err(DecodeError::DepthExceeded { max: 2 })));
+/// ```
+///
+/// Each container is decoded from exactly the bytes its size declares,
+/// so the pad byte following an odd-sized one is left to the level
+/// holding it rather than read as the start of another child:
+/// ```
+/// use std::io::Cursor;
+/// use iffc::{Decoder, GroupingDecoder, Entry, Node, Chunk, FourCC};
+///
+/// let inp = b"RIFF\x24\x00\x00\x00WAVE\
+///             LIST\x0D\x00\x00\x00INFO\
+///             INAM\x01\x00\x00\x00x\0\
+///             next\x02\x00\x00\x00ab\
+///             tail\x00\x00\x00\x00";
+///
+/// let tree = Decoder::new(&inp[..]).into_tree().unwrap();
+///
+/// assert_eq!(tree, [
+///     Node::Branch { id: FourCC(*b"RIFF"), form_type: FourCC(*b"WAVE"), children: vec![
+///         Node::Branch { id: FourCC(*b"LIST"), form_type: FourCC(*b"INFO"), children: vec![
+///             Node::Leaf(Chunk(FourCC(*b"INAM"), Box::new(*b"x")))
+///         ]},
+///         Node::Leaf(Chunk(FourCC(*b"next"), Box::new(*b"ab")))
+///     ]},
+///     Node::Leaf(Chunk(FourCC(*b"tail"), Box::new([])))
+/// ]);
+///
+/// // the same goes for a `GroupingDecoder`.
+/// let mut dec = GroupingDecoder::new(Cursor::new(&inp[..]));
+/// let riff = match dec.next().unwrap().unwrap() { Entry::Group(g) => g, e => panic!("{:?}", e) };
+///
+/// let mut children = dec.children(&riff);
+/// let list = match children.next().unwrap().unwrap() { Entry::Group(g) => g, e => panic!("{:?}", e) };
+/// assert_eq!(children.children(&list).count(), 1);
+///
+/// assert_eq!(children.next().unwrap().unwrap(), Entry::Leaf(Chunk(FourCC(*b"next"), Box::new(*b"ab"))));
+/// assert!(children.next().is_none());
+/// ```
+///
+/// An EA IFF `CAT ` is descended into like any other container, its
+/// contents type taking the place of the form type, so each of the
+/// `FORM`s (or other containers) it concatenates becomes a branch:
+/// ```
+/// use std::io::Cursor;
+/// use iffc::{Decoder, Encoder, Node, Chunk, FourCC, Endianness};
+///
+/// let mut enc = Encoder::new(Cursor::new(Vec::new())).with_endianness(Endianness::Big);
+/// let mut cat = enc.begin_group(FourCC(*b"CAT "), FourCC(*b"ILBM"))?;
+///
+/// for name in [b"one ", b"two "] {
+///     let mut form = cat.begin_group(FourCC(*b"FORM"), FourCC(*b"ILBM"))?;
+///     form.write_chunk(&Chunk(FourCC(*b"NAME"), Box::new(*name)))?;
+///     form.finish()?;
+/// }
+///
+/// let mut list = cat.begin_group(FourCC(*b"LIST"), FourCC(*b"ILBM"))?;
+/// list.write_chunk(&Chunk(FourCC(*b"PROP"), Box::new([])))?;
+/// list.finish()?;
+/// cat.finish()?;
+///
+/// // an empty one, holding nothing but its contents type.
+/// enc.begin_group(FourCC(*b"CAT "), FourCC(*b"    "))?.finish()?;
+///
+/// let out = enc.into_inner().into_inner();
+/// let tree = Decoder::new(&out[..]).with_endianness(Endianness::Big).into_tree().unwrap();
+///
+/// let form = |name: &[u8; 4]| Node::Branch { id: FourCC(*b"FORM"), form_type: FourCC(*b"ILBM"), children: vec![
+///     Node::Leaf(Chunk(FourCC(*b"NAME"), Box::new(*name)))
+/// ]};
+///
+/// assert_eq!(tree, [
+///     Node::Branch { id: FourCC(*b"CAT "), form_type: FourCC(*b"ILBM"), children: vec![
+///         form(b"one "),
+///         form(b"two "),
+///         Node::Branch { id: FourCC(*b"LIST"), form_type: FourCC(*b"ILBM"), children: vec![
+///             Node::Leaf(Chunk(FourCC(*b"PROP"), Box::new([])))
+///         ]}
+///     ]},
+///     Node::Branch { id: FourCC(*b"CAT "), form_type: FourCC(*b"    "), children: vec![] }
+/// ]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct TreeDecoder<R: Read, L: ChunkLayout = Layout> {
+    decoder: Decoder<R, L>,
+    max_depth: usize,
+    is_container: Box<dyn Fn(FourCC) -> bool>
+}
+
+impl<R: Read, L: ChunkLayout> TreeDecoder<R, L> {
+    /// Builds trees out of the chunks of `decoder`.
+    pub fn new(decoder: Decoder<R, L>) -> Self {
+        Self {
+            decoder,
+            max_depth: 64,
+            is_container: Box::new(Group::is_container)
+        }
+    }
+
+    /// Allows at most `max` levels of nested containers.
+    pub fn with_max_depth(mut self, max: usize) -> Self
+    { self.max_depth = max; self }
+
+    /// Treats the chunks whose id satisfies `f` as containers, instead
+    /// of those known to `Group::is_container`.
+    pub fn with_containers<F: Fn(FourCC) -> bool + 'static>(mut self, f: F) -> Self
+    { self.is_container = Box::new(f); self }
+
+    /// Decodes the entire stream into a tree.
+    pub fn decode(mut self) -> Result<Vec<Node>, DecodeError> {
+        let mut nodes = Vec::new();
+
+        while let Some(chunk) = self.decoder.next() {
+            let chunk = chunk?;
+            let (node, chunks) = self.node(&self.decoder, chunk, 1)?;
+
+            self.decoder.set_chunk_count(chunks);
+            nodes.push(node);
+        }
+
+        Ok(nodes)
+    }
+
+    /// Turns `chunk` into a node, `depth` being the nesting level it
+    /// would have as a container, along with the number of chunks read
+    /// once `parent` and its descendants have read it.
+    fn node<D: Read>(&self, parent: &Decoder<D, L>, chunk: Chunk, depth: usize) -> Result<(Node, u32), DecodeError> {
+        if !(self.is_container)(chunk.0)
+        { return Ok((Node::Leaf(chunk), parent.chunk_count())) }
+
+        if depth > self.max_depth
+        { return Err(DecodeError::DepthExceeded { max: self.max_depth }) }
+
+        if chunk.1.len() < 4
+        { return Err(DecodeError::UnexpectedEof { expected: 4, got: chunk.1.len() }) }
+
+        let mut form_type = [0u8; 4];
+        form_type.copy_from_slice(&chunk.1[..4]);
+
+        let mut children = Vec::new();
+        let mut dec = parent.sub_decoder(&chunk.1[4..]);
+
+        while let Some(child) = dec.next() {
+            let child = child?;
+            let (node, chunks) = self.node(&dec, child, depth + 1)?;
+
+            dec.set_chunk_count(chunks);
+            children.push(node);
+        }
+
+        Ok((Node::Branch { id: chunk.0, form_type: FourCC(form_type), children }, dec.chunk_count()))
+    }
+}
+
+impl<R: Read, L: ChunkLayout> Decoder<R, L> {
+    /// Decodes the entire stream into a tree of chunks with the
+    /// defaults of `TreeDecoder`.
+    pub fn into_tree(self) -> Result<Vec<Node>, DecodeError>
+    { TreeDecoder::new(self).decode() }
+}
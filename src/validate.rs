@@ -0,0 +1,180 @@
+use std::io::{Read, Seek, SeekFrom};
+use crate::{FourCC, DecodeError, Group, ChunkLayout};
+use crate::layout;
+
+/// Something `validate` found wrong with the structure of a file, at
+/// the byte `offset` where it starts.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Issue {
+    /// The `id` of a chunk isn't printable ASCII.
+    InvalidId { id: FourCC, offset: u64 },
+    /// A chunk declares a body of `size` bytes (and its checksum, if
+    /// any) while only `left` bytes remain in the container, or the
+    /// stream, holding it.
+    Overrun { id: FourCC, offset: u64, size: u32, left: u64 },
+    /// A container is too small to even hold its form type.
+    MissingFormType { id: FourCC, offset: u64 },
+    /// `len` bytes, too few to be a chunk, follow the last chunk of a
+    /// container or of the stream.
+    TrailingData { offset: u64, len: u64 },
+    /// The pad byte of an odd body isn't zero but `value`. Only a
+    /// warning.
+    NonZeroPad { offset: u64, value: u8 },
+    /// The pad byte of an odd body is missing at the end of a container
+    /// or of the stream. Only a warning.
+    MissingPad { offset: u64 },
+    /// A top-level chunk which isn't one of the containers known to
+    /// `Group::is_container`. Only a warning.
+    UnknownContainer { id: FourCC, offset: u64 },
+    /// A container is nested more than `max` levels deep, as a
+    /// `TreeDecoder` allows by default. Its children aren't checked.
+    TooDeep { id: FourCC, offset: u64, max: usize }
+}
+
+/// Deepest level of containers `validate` descends into.
+const MAX_DEPTH: usize = 64;
+
+/// Outcome of `validate`: the structure is valid as long as there are
+/// no `errors`, whatever the `warnings`.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ValidationReport {
+    pub warnings: Vec<Issue>,
+    pub errors: Vec<Issue>
+}
+
+impl ValidationReport {
+    /// Whether no errors were found.
+    pub fn is_valid(&self) -> bool { self.errors.is_empty() }
+}
+
+/// Walks the whole structure of a file, from the position of `r` to
+/// its end, without reading any of the bodies except for the form type
+/// of containers. Every container must be exactly filled by its
+/// children, ids must be printable and nothing but padding may follow
+/// the last chunk. Checksums are skipped over rather than verified.
+/// Only the reader failing is an `Err`, whatever is wrong with the
+/// structure ends up in the report.
+/// ```
+/// use std::io::Cursor;
+/// use iffc::{validate, Issue, Layout, FourCC};
+///
+/// let valid = b"RIFF\x1A\x00\x00\x00WAVE\
+///               fmt \x03\x00\x00\x00abc\0\
+///               data\x02\x00\x00\x00de";
+///
+/// let report = validate(Cursor::new(&valid[..]), Layout::RIFF)?;
+/// assert!(report.is_valid());
+/// assert!(report.warnings.is_empty());
+///
+/// // the container claims two bytes more than its children take up.
+/// let mut mismatched = valid.to_vec();
+/// mismatched[4] += 2;
+/// mismatched.extend_from_slice(b"xy");
+///
+/// let report = validate(Cursor::new(mismatched), Layout::RIFF)?;
+/// assert_eq!(report.errors, [Issue::TrailingData { offset: 34, len: 2 }]);
+///
+/// // or two bytes fewer, so its last child runs past its end.
+/// let mut mismatched = valid.to_vec();
+/// mismatched[4] -= 2;
+///
+/// let report = validate(Cursor::new(mismatched), Layout::RIFF)?;
+/// assert_eq!(report.errors, [
+///     Issue::Overrun { id: FourCC(*b"data"), offset: 24, size: 2, left: 0 },
+///     Issue::TrailingData { offset: 32, len: 2 }
+/// ]);
+///
+/// // junk after the end of the file.
+/// let mut junk = valid.to_vec();
+/// junk.extend_from_slice(b"\0\0\0");
+///
+/// let report = validate(Cursor::new(junk), Layout::RIFF)?;
+/// assert_eq!(report.errors, [Issue::TrailingData { offset: 34, len: 3 }]);
+///
+/// // containers nested too deeply to walk without risking the stack.
+/// let mut nested = Vec::new();
+/// for level in 0..100u32 {
+///     nested.extend_from_slice(b"LIST");
+///     nested.extend_from_slice(&(4 + 12 * (99 - level)).to_le_bytes());
+///     nested.extend_from_slice(b"INFO");
+/// }
+///
+/// let report = validate(Cursor::new(nested), Layout::RIFF)?;
+/// assert_eq!(report.errors, [Issue::TooDeep { id: FourCC(*b"LIST"), offset: 64 * 12, max: 64 }]);
+/// # Ok::<(), iffc::DecodeError>(())
+/// ```
+pub fn validate<R: Read + Seek, L: ChunkLayout>(mut r: R, layout: L) -> Result<ValidationReport, DecodeError> {
+    let start = r.stream_position()?;
+    let end = r.seek(SeekFrom::End(0))?;
+
+    let mut v = Validator { r, layout, report: ValidationReport::default() };
+    v.walk(start, end, 0)?;
+
+    Ok(v.report)
+}
+
+struct Validator<R, L> {
+    r: R,
+    layout: L,
+    report: ValidationReport
+}
+
+impl<R: Read + Seek, L: ChunkLayout> Validator<R, L> {
+    /// Checks the chunks between `pos` and `end`, which are the children
+    /// of a container nested `depth` levels deep, `0` being the top.
+    fn walk(&mut self, mut pos: u64, end: u64, depth: usize) -> Result<(), DecodeError> {
+        while pos < end {
+            if end - pos < 8 {
+                self.report.errors.push(Issue::TrailingData { offset: pos, len: end - pos });
+                return Ok(())
+            }
+
+            let mut header = [0u8; 8];
+            self.r.seek(SeekFrom::Start(pos))?;
+            self.r.read_exact(&mut header)?;
+
+            let (id, size) = layout::parse_header(&self.layout, &header);
+            let body = pos + 8;
+            let framed = size as u64 + layout::trailer_len(&self.layout) as u64;
+
+            if !id.is_printable()
+            { self.report.errors.push(Issue::InvalidId { id, offset: pos }) }
+
+            if framed > end - body {
+                self.report.errors.push(Issue::Overrun { id, offset: pos, size, left: end - body });
+                return Ok(())
+            }
+
+            if Group::is_container(id) {
+                if size < 4 {
+                    self.report.errors.push(Issue::MissingFormType { id, offset: pos })
+                } else if depth == MAX_DEPTH {
+                    self.report.errors.push(Issue::TooDeep { id, offset: pos, max: MAX_DEPTH })
+                } else {
+                    self.walk(body + 4, body + size as u64, depth + 1)?
+                }
+            } else if depth == 0 {
+                self.report.warnings.push(Issue::UnknownContainer { id, offset: pos })
+            }
+
+            let next = body + framed;
+            let pad = self.layout.alignment().padding(size as usize) as u64;
+
+            if pad > end - next {
+                self.report.warnings.push(Issue::MissingPad { offset: next });
+                return Ok(())
+            }
+
+            let mut padding = [0u8; 3];
+            self.r.seek(SeekFrom::Start(next))?;
+            self.r.read_exact(&mut padding[..pad as usize])?;
+
+            if let Some(&value) = padding.iter().find(|&&b| b != 0)
+            { self.report.warnings.push(Issue::NonZeroPad { offset: next, value }) }
+
+            pos = next + pad;
+        }
+
+        Ok(())
+    }
+}
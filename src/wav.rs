@@ -0,0 +1,395 @@
+//! Typed views of the chunks of a `WAVE` file.
+#[cfg(feature = "wav")]
+use core::slice::ChunksExact;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Write, Seek};
+use crate::FormatError;
+#[cfg(feature = "std")]
+use crate::{Chunk, BorrowedChunk, ContainerWriter, ids};
+
+/// Format tag of integer samples.
+pub const WAVE_FORMAT_PCM: u16 = 1;
+
+/// Format tag of floating-point samples.
+pub const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// Format tag of `Fmt` whose extension is laid out as an `Extensible`.
+pub const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// The body of a `fmt ` chunk, describing how the samples of the
+/// `data` chunk are encoded. All of its fields are little-endian.
+///
+/// ```
+/// use iffc::wav::{Fmt, Extensible};
+/// use iffc::FormatError;
+///
+/// // 16-bit stereo PCM at 44.1 kHz.
+/// let pcm = b"\x01\x00\x02\x00\x44\xAC\x00\x00\x10\xB1\x02\x00\x04\x00\x10\x00";
+/// let fmt = Fmt::parse(pcm).unwrap();
+///
+/// assert_eq!((fmt.format_tag, fmt.channels, fmt.sample_rate), (1, 2, 44100));
+/// assert_eq!((fmt.byte_rate, fmt.block_align, fmt.bits_per_sample), (176400, 4, 16));
+/// assert_eq!((fmt.extension_size, fmt.extensible), (None, None));
+///
+/// // the same as WAVE_FORMAT_EXTENSIBLE, with 24 valid bits out of 32.
+/// let mut ext = pcm.to_vec();
+/// ext[..2].copy_from_slice(&[0xFE, 0xFF]);
+/// ext.extend_from_slice(b"\x16\x00\x18\x00\x03\x00\x00\x00");
+/// ext.extend_from_slice(&[0x11; 16]);
+///
+/// let fmt = Fmt::parse(&ext).unwrap();
+/// assert_eq!(fmt.extension_size, Some(22));
+/// assert_eq!(fmt.extensible, Some(Extensible {
+///     valid_bits_per_sample: 24,
+///     channel_mask: 3,
+///     sub_format: [0x11; 16]
+/// }));
+///
+/// assert_eq!(Fmt::parse(&ext[..30]), Err(FormatError::TooShort { expected: 40, got: 30 }));
+/// assert_eq!(Fmt::parse(&pcm[..12]), Err(FormatError::TooShort { expected: 16, got: 12 }));
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Fmt {
+    pub format_tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub byte_rate: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+    /// Size of the extension which follows, if the body has room for
+    /// the field at all.
+    pub extension_size: Option<u16>,
+    /// Extension of `WAVE_FORMAT_EXTENSIBLE` formats.
+    pub extensible: Option<Extensible>
+}
+
+/// Extension of a `Fmt` tagged `WAVE_FORMAT_EXTENSIBLE`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Extensible {
+    pub valid_bits_per_sample: u16,
+    pub channel_mask: u32,
+    /// GUID of the actual format, whose first two bytes are the format
+    /// tag it would have otherwise.
+    pub sub_format: [u8; 16]
+}
+
+fn u16_at(b: &[u8], at: usize) -> u16
+{ u16::from_le_bytes([b[at], b[at + 1]]) }
+
+fn u32_at(b: &[u8], at: usize) -> u32
+{ u32::from_le_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]]) }
+
+impl Fmt {
+    /// Parses the body of a `fmt ` chunk, which must be 16 bytes long
+    /// at least, or 40 bytes for `WAVE_FORMAT_EXTENSIBLE`.
+    pub fn parse(body: &[u8]) -> Result<Self, FormatError> {
+        let need = |expected: usize| if body.len() < expected {
+            Err(FormatError::TooShort { expected, got: body.len() })
+        } else { Ok(()) };
+
+        need(16)?;
+
+        let format_tag = u16_at(body, 0);
+        let extension_size = if body.len() >= 18 { Some(u16_at(body, 16)) } else { None };
+
+        let extensible = if format_tag == WAVE_FORMAT_EXTENSIBLE {
+            need(40)?;
+
+            let mut sub_format = [0u8; 16];
+            sub_format.copy_from_slice(&body[24..40]);
+
+            Some(Extensible {
+                valid_bits_per_sample: u16_at(body, 18),
+                channel_mask: u32_at(body, 20),
+                sub_format
+            })
+        } else { None };
+
+        Ok(Self {
+            format_tag,
+            channels: u16_at(body, 2),
+            sample_rate: u32_at(body, 4),
+            byte_rate: u32_at(body, 8),
+            block_align: u16_at(body, 12),
+            bits_per_sample: u16_at(body, 14),
+            extension_size,
+            extensible
+        })
+    }
+
+    /// Encodes the fields back into the body of a `fmt ` chunk, the
+    /// reverse of `parse`. The extension is written as long as its
+    /// `extension_size` says, zero-filled past the `extensible` fields.
+    /// ```
+    /// use iffc::wav::Fmt;
+    ///
+    /// let pcm = b"\x01\x00\x02\x00\x44\xAC\x00\x00\x10\xB1\x02\x00\x04\x00\x10\x00";
+    /// assert_eq!(Fmt::parse(pcm).unwrap().to_bytes(), pcm);
+    ///
+    /// let mut ext = pcm.to_vec();
+    /// ext[..2].copy_from_slice(&[0xFE, 0xFF]);
+    /// ext.extend_from_slice(b"\x16\x00\x18\x00\x03\x00\x00\x00");
+    /// ext.extend_from_slice(&[0x11; 16]);
+    /// assert_eq!(Fmt::parse(&ext).unwrap().to_bytes(), ext);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(18);
+        body.extend_from_slice(&self.format_tag.to_le_bytes());
+        body.extend_from_slice(&self.channels.to_le_bytes());
+        body.extend_from_slice(&self.sample_rate.to_le_bytes());
+        body.extend_from_slice(&self.byte_rate.to_le_bytes());
+        body.extend_from_slice(&self.block_align.to_le_bytes());
+        body.extend_from_slice(&self.bits_per_sample.to_le_bytes());
+
+        if let Some(size) = self.extension_size {
+            body.extend_from_slice(&size.to_le_bytes());
+
+            if let Some(ext) = self.extensible {
+                body.extend_from_slice(&ext.valid_bits_per_sample.to_le_bytes());
+                body.extend_from_slice(&ext.channel_mask.to_le_bytes());
+                body.extend_from_slice(&ext.sub_format);
+            }
+
+            body.resize(18 + size as usize, 0);
+        }
+
+        body
+    }
+
+    /// The format tag, or the one of the sub-format if the format is
+    /// `WAVE_FORMAT_EXTENSIBLE`.
+    pub fn sample_format(&self) -> u16 {
+        match self.extensible {
+            Some(ext) => u16::from_le_bytes([ext.sub_format[0], ext.sub_format[1]]),
+            None => self.format_tag
+        }
+    }
+}
+
+/// Writes a whole `WAVE` file made of a `fmt ` chunk describing `pcm`
+/// as `fmt` and a `data` chunk holding it, the size of the `RIFF` being
+/// patched once both are written. An odd `pcm` is followed by a pad
+/// byte, which the size of the `RIFF` spans.
+/// ```
+/// use std::io::Cursor;
+/// use iffc::{Decoder, FourCC};
+/// use iffc::wav::{self, Fmt};
+///
+/// // 8-bit mono at 8 kHz, of an odd number of samples.
+/// let fmt = Fmt::parse(b"\x01\x00\x01\x00\x40\x1F\x00\x00\x40\x1F\x00\x00\x01\x00\x08\x00").unwrap();
+/// let pcm = [0x80, 0xFF, 0x00];
+///
+/// let mut out = Cursor::new(Vec::new());
+/// wav::write_pcm(&mut out, &fmt, &pcm)?;
+///
+/// let out = out.into_inner();
+/// assert_eq!(out.len(), 12 + 24 + 12);
+/// assert_eq!(&out[4..8], &(out.len() as u32 - 8).to_le_bytes());
+///
+/// let mut dec = Decoder::new(&out[..]);
+/// assert_eq!(dec.read_form_type()?, FourCC(*b"WAVE"));
+///
+/// let (fmt_chunk, data) = (dec.next().unwrap()?, dec.next().unwrap()?);
+/// assert_eq!(Fmt::parse(&fmt_chunk.1).unwrap(), fmt);
+/// assert_eq!(&data.1[..], &pcm[..]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn write_pcm<W: Write + Seek>(w: W, fmt: &Fmt, pcm: &[u8]) -> io::Result<()> {
+    let mut riff = ContainerWriter::new(w, ids::RIFF, ids::WAVE)?;
+
+    riff.push(&Chunk(ids::FMT, fmt.to_bytes().into()))?;
+    riff.write_chunk_ref(BorrowedChunk(ids::DATA, pcm))?;
+    riff.finish().map(drop)
+}
+
+/// The body of a `bext` chunk of a Broadcast Wave file (EBU Tech 3285),
+/// whose text fields are cut at their first NUL and any bytes of them
+/// which aren't UTF-8 replaced.
+/// ```
+/// use iffc::wav::Bext;
+/// use iffc::FormatError;
+///
+/// let mut body = vec![0u8; 602];
+/// body[..5].copy_from_slice(b"Take1");
+/// body[256..262].copy_from_slice(b"Studio");
+/// body[320..338].copy_from_slice(b"2021-03-0412:34:56");
+/// body[338..346].copy_from_slice(&(48000u64 * 3600).to_le_bytes());
+/// body.extend_from_slice(b"A=PCM,F=48000,W=24,M=stereo\r\n");
+///
+/// let bext = Bext::parse(&body).unwrap();
+/// assert_eq!((&bext.description[..], &bext.originator[..]), ("Take1", "Studio"));
+/// assert_eq!((&bext.origination_date[..], &bext.origination_time[..]), ("2021-03-04", "12:34:56"));
+/// assert_eq!(bext.time_reference, 172_800_000);
+/// assert_eq!(bext.coding_history, "A=PCM,F=48000,W=24,M=stereo\r\n");
+/// assert_eq!((bext.version, bext.umid, bext.loudness), (0, None, None));
+///
+/// // version 2 adds the loudness of the programme, in hundredths.
+/// body[346] = 2;
+/// body[412..414].copy_from_slice(&(-2300i16).to_le_bytes());
+/// assert_eq!(Bext::parse(&body).unwrap().loudness.unwrap().value, -2300);
+///
+/// assert_eq!(Bext::parse(&body[..600]), Err(FormatError::TooShort { expected: 602, got: 600 }));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Bext {
+    pub description: String,
+    pub originator: String,
+    pub originator_reference: String,
+    /// As `yyyy-mm-dd`.
+    pub origination_date: String,
+    /// As `hh:mm:ss`.
+    pub origination_time: String,
+    /// Samples since midnight preceding the first one of the file.
+    pub time_reference: u64,
+    pub version: u16,
+    /// SMPTE UMID, from version 1 on.
+    pub umid: Option<[u8; 64]>,
+    /// From version 2 on.
+    pub loudness: Option<Loudness>,
+    /// Lines describing each process the audio went through, as long
+    /// as the rest of the body.
+    pub coding_history: String
+}
+
+/// Loudness fields of a `Bext`, in hundredths of LU or LUFS (dBTP for
+/// the true peak) as EBU R 128 measures them.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Loudness {
+    pub value: i16,
+    pub range: i16,
+    pub max_true_peak: i16,
+    pub max_momentary: i16,
+    pub max_short_term: i16
+}
+
+/// Text of a fixed-size field, up to its first NUL.
+fn text(b: &[u8]) -> String {
+    let end = b.iter().position(|&c| c == 0).unwrap_or(b.len());
+    String::from_utf8_lossy(&b[..end]).into_owned()
+}
+
+impl Bext {
+    /// Parses the body of a `bext` chunk, which must hold the 602 bytes
+    /// of fixed fields at least. The UMID and loudness of versions
+    /// older than the one they appeared in, reserved then, are `None`.
+    pub fn parse(body: &[u8]) -> Result<Self, FormatError> {
+        if body.len() < 602
+        { return Err(FormatError::TooShort { expected: 602, got: body.len() }) }
+
+        let version = u16_at(body, 346);
+        let i16_at = |at: usize| u16_at(body, at) as i16;
+
+        let umid = if version >= 1 {
+            let mut umid = [0u8; 64];
+            umid.copy_from_slice(&body[348..412]);
+            Some(umid)
+        } else { None };
+
+        let loudness = if version >= 2 {
+            Some(Loudness {
+                value: i16_at(412),
+                range: i16_at(414),
+                max_true_peak: i16_at(416),
+                max_momentary: i16_at(418),
+                max_short_term: i16_at(420)
+            })
+        } else { None };
+
+        Ok(Self {
+            description: text(&body[..256]),
+            originator: text(&body[256..288]),
+            originator_reference: text(&body[288..320]),
+            origination_date: text(&body[320..330]),
+            origination_time: text(&body[330..338]),
+            time_reference: u32_at(body, 338) as u64 | (u32_at(body, 342) as u64) << 32,
+            version,
+            umid,
+            loudness,
+            coding_history: text(&body[602..])
+        })
+    }
+}
+
+/// A single sample of a `data` chunk, 24-bit ones being sign-extended.
+#[cfg(feature = "wav")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sample { U8(u8), I16(i16), I24(i32), I32(i32), F32(f32), F64(f64) }
+
+/// Iterator over the samples of a `data` chunk, see `samples`.
+#[cfg(feature = "wav")]
+pub struct Samples<'a> {
+    blocks: ChunksExact<'a, u8>,
+    decode: fn(&[u8]) -> Sample
+}
+
+/// Decodes the samples of the body of a `data` chunk as described by
+/// `fmt`, which are interleaved: the first sample of each channel, then
+/// the second one, and so on. Integer (`WAVE_FORMAT_PCM`) samples of 8,
+/// 16, 24 or 32 bits and floating-point ones of 32 or 64 bits are
+/// supported, any other format is `FormatError::Unsupported`. Samples
+/// which are padded to more bytes than their bits take, according to
+/// the `block_align` of `fmt`, have their padding skipped. A partial
+/// sample at the end of `data` is left out.
+/// ```
+/// use iffc::wav::{samples, Fmt, Sample};
+///
+/// // 16-bit stereo.
+/// let fmt = Fmt::parse(b"\x01\x00\x02\x00\x44\xAC\x00\x00\x10\xB1\x02\x00\x04\x00\x10\x00").unwrap();
+/// let data = b"\x01\x00\xFF\xFF\x00\x80\xFF\x7F";
+///
+/// let frames = samples(&fmt, data).unwrap().collect::<Vec<_>>();
+/// assert_eq!(frames.chunks(2).collect::<Vec<_>>(), [
+///     [Sample::I16(1), Sample::I16(-1)],
+///     [Sample::I16(i16::MIN), Sample::I16(i16::MAX)]
+/// ]);
+///
+/// // 24-bit mono, packed into 3 bytes each.
+/// let fmt = Fmt::parse(b"\x01\x00\x01\x00\x80\xBB\x00\x00\x80\x32\x02\x00\x03\x00\x18\x00").unwrap();
+/// let data = b"\x01\x00\x00\xFF\xFF\xFF\x00\x00\x80\xAA";
+///
+/// assert_eq!(samples(&fmt, data).unwrap().collect::<Vec<_>>(), [
+///     Sample::I24(1), Sample::I24(-1), Sample::I24(-1 << 23)
+/// ]);
+/// ```
+#[cfg(feature = "wav")]
+pub fn samples<'a>(fmt: &Fmt, data: &'a [u8]) -> Result<Samples<'a>, FormatError> {
+    let decode: fn(&[u8]) -> Sample = match (fmt.sample_format(), fmt.bits_per_sample) {
+        (WAVE_FORMAT_PCM, 8)  => |b| Sample::U8(b[0]),
+        (WAVE_FORMAT_PCM, 16) => |b| Sample::I16(i16::from_le_bytes([b[0], b[1]])),
+        (WAVE_FORMAT_PCM, 24) => |b| Sample::I24(i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8),
+        (WAVE_FORMAT_PCM, 32) => |b| Sample::I32(i32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => |b| Sample::F32(f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        (WAVE_FORMAT_IEEE_FLOAT, 64) => |b| {
+            let mut n = [0u8; 8];
+            n.copy_from_slice(&b[..8]);
+            Sample::F64(f64::from_le_bytes(n))
+        },
+        (format_tag, bits_per_sample) =>
+            return Err(FormatError::Unsupported { format_tag, bits_per_sample })
+    };
+
+    let width = fmt.bits_per_sample as usize / 8;
+    let stride = match (fmt.block_align as usize).checked_div(fmt.channels as usize) {
+        Some(n) if n >= width && fmt.block_align % fmt.channels == 0 => n,
+        _ => width
+    };
+
+    Ok(Samples { blocks: data.chunks_exact(stride), decode })
+}
+
+#[cfg(feature = "wav")]
+impl Iterator for Samples<'_> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample>
+    { self.blocks.next().map(self.decode) }
+
+    fn size_hint(&self) -> (usize, Option<usize>)
+    { self.blocks.size_hint() }
+}
+
+#[cfg(feature = "wav")]
+impl ExactSizeIterator for Samples<'_> {}
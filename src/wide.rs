@@ -0,0 +1,234 @@
+use std::io::{self, Read, Write};
+use std::convert::TryFrom;
+use std::fmt;
+use crate::{FourCC, DecodeError, ChunkLayout, Layout, Endianness, FieldOrder, Crc32, DEFAULT_MAX_CHUNK_SIZE};
+use crate::decoder::read_upto;
+use crate::encoder::write_tail;
+use crate::layout::trailer_len;
+
+/// Eight-byte code identifying the chunks of the few IFF derivatives
+/// which widened their ids, read and written by `WideDecoder` and
+/// `WideEncoder`. Shown as text the same way as a `FourCC`.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct EightCC(pub [u8; 8]);
+
+/// A chunk with an `EightCC` id, see `EightCC`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct WideChunk(pub EightCC, pub Box<[u8]>);
+
+impl EightCC {
+    /// The first four bytes of the code, which stand for the whole of
+    /// it in the `DecodeError`s of a `WideDecoder`.
+    pub fn prefix(&self) -> FourCC
+    { FourCC([self.0[0], self.0[1], self.0[2], self.0[3]]) }
+
+    fn suffix(&self) -> FourCC
+    { FourCC([self.0[4], self.0[5], self.0[6], self.0[7]]) }
+}
+
+impl From<[u8; 8]> for EightCC
+{ fn from(b: [u8; 8]) -> Self { Self(b) } }
+
+impl fmt::Display for EightCC {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.prefix().write_escaped(f, &[])?;
+        self.suffix().write_escaped(f, &[])
+    }
+}
+
+impl fmt::Debug for EightCC {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"")?;
+        self.prefix().write_escaped(f, b"\"")?;
+        self.suffix().write_escaped(f, b"\"")?;
+        f.write_str("\"")
+    }
+}
+
+/// Splits a 12-byte header into its id and its size.
+fn parse_header<L: ChunkLayout>(layout: &L, header: &[u8; 12]) -> (EightCC, u32) {
+    let (id_at, size_at) = match layout.field_order() {
+        FieldOrder::IdThenSize => (0, 8),
+        FieldOrder::SizeThenId => (4, 0)
+    };
+
+    let mut id = [0u8; 8];
+    let mut size = [0u8; 4];
+    id.copy_from_slice(&header[id_at..id_at + 8]);
+    size.copy_from_slice(&header[size_at..size_at + 4]);
+
+    (EightCC(id), layout.endianness().read_u32(size))
+}
+
+/// Decodes chunks with eight-byte ids, otherwise framed as `layout`
+/// describes: the size field is still four bytes and the layout's
+/// field order, endianness, padding and checksum apply as they would
+/// to a `Decoder`, whose `Chunk`s only have room for a `FourCC`.
+/// ```
+/// use iffc::{WideDecoder, WideEncoder, WideChunk, EightCC, Layout};
+///
+/// let chunk = WideChunk(EightCC(*b"HEADER01"), Box::new(*b"odd"));
+///
+/// let mut enc = WideEncoder::new(Vec::new()).with_layout(Layout::IFF);
+/// enc.write_chunk(&chunk)?;
+/// enc.write_chunk(&WideChunk(EightCC(*b"BODY\0\0\0\0"), Box::new([])))?;
+///
+/// let out = enc.into_inner();
+/// assert_eq!(&out[..16], b"HEADER01\x00\x00\x00\x03odd\0");
+///
+/// let back = WideDecoder::new(&out[..]).with_layout(Layout::IFF).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(back, [chunk, WideChunk(EightCC(*b"BODY\0\0\0\0"), Box::new([]))]);
+/// assert_eq!(format!("{:?}", back[1].0), "\"BODY\\x00\\x00\\x00\\x00\"");
+///
+/// // the header is twelve bytes, so a four-byte id would misread it.
+/// assert!(WideDecoder::new(&out[..10]).next().unwrap().is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct WideDecoder<R: Read, L: ChunkLayout = Layout> {
+    r: R,
+    layout: L,
+    max_chunk_size: u32,
+    pos: u64,
+    done: bool
+}
+
+impl<R: Read> WideDecoder<R> {
+    /// Decodes little-endian (RIFF) chunks, as `Decoder::new` does.
+    pub fn new(r: R) -> Self
+    { Self { r, layout: Layout::RIFF, max_chunk_size: DEFAULT_MAX_CHUNK_SIZE, pos: 0, done: false } }
+}
+
+impl<R: Read, L: ChunkLayout> WideDecoder<R, L> {
+    /// Frames chunks as described by `layout` instead.
+    pub fn with_layout<M: ChunkLayout>(self, layout: M) -> WideDecoder<R, M> {
+        WideDecoder { r: self.r, layout, max_chunk_size: self.max_chunk_size, pos: self.pos, done: self.done }
+    }
+
+    /// Same as `Decoder::with_max_chunk_size`.
+    pub fn with_max_chunk_size(mut self, max: u32) -> Self
+    { self.max_chunk_size = max; self }
+
+    /// Unwraps the underlying reader.
+    pub fn into_inner(self) -> R { self.r }
+
+    fn read_chunk(&mut self) -> Result<Option<WideChunk>, DecodeError> {
+        let mut header = [0u8; 12];
+        let offset = self.pos;
+
+        let n = read_upto(&mut self.r, &mut header)?;
+        self.pos += n as u64;
+
+        match n {
+            0 => return Ok(None),
+            12 => {},
+            n => return Err(DecodeError::UnexpectedEof { expected: 12, got: n })
+        }
+
+        let (id, size) = parse_header(&self.layout, &header);
+
+        if size > self.max_chunk_size
+        { return Err(DecodeError::ChunkTooLarge { size, max: self.max_chunk_size }) }
+
+        if usize::try_from(size).is_err()
+        { return Err(DecodeError::SizeOverflow { id: id.prefix(), size }) }
+
+        let mut data = Vec::new();
+        (&mut self.r).take(size as u64).read_to_end(&mut data)?;
+        self.pos += data.len() as u64;
+
+        if data.len() != size as usize
+        { return Err(DecodeError::TruncatedBody { id: id.prefix(), offset, expected: size, got: data.len() }) }
+
+        let trailer = trailer_len(&self.layout);
+        let mut crc = [0u8; 4];
+
+        let n = read_upto(&mut self.r, &mut crc[..trailer])?;
+        self.pos += n as u64;
+
+        if n != trailer
+        { return Err(DecodeError::UnexpectedEof { expected: trailer, got: n }) }
+
+        if trailer != 0 {
+            let mut expected = Crc32::new();
+            expected.update(&id.0);
+            expected.update(&data);
+
+            let expected = expected.finish();
+            let found = Endianness::Big.read_u32(crc);
+
+            if expected != found
+            { return Err(DecodeError::BadChecksum { expected, found }) }
+        }
+
+        // a missing pad byte after the last chunk is tolerated, as
+        // by `Decoder`.
+        let mut pad = [0u8; 3];
+        self.pos += read_upto(&mut self.r, &mut pad[..self.layout.alignment().padding(data.len())])? as u64;
+
+        Ok(Some(WideChunk(id, data.into_boxed_slice())))
+    }
+}
+
+impl<R: Read, L: ChunkLayout> Iterator for WideDecoder<R, L> {
+    type Item = Result<WideChunk, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done { return None }
+
+        let chunk = self.read_chunk();
+        self.done = !matches!(chunk, Ok(Some(_)));
+        chunk.transpose()
+    }
+}
+
+/// Encodes chunks with eight-byte ids, the counterpart of `WideDecoder`.
+pub struct WideEncoder<W: Write, L: ChunkLayout = Layout> {
+    w: W,
+    layout: L,
+    pad: u8
+}
+
+impl<W: Write> WideEncoder<W> {
+    /// Encodes little-endian (RIFF) chunks, as `Encoder::new` does.
+    pub fn new(w: W) -> Self
+    { Self { w, layout: Layout::RIFF, pad: 0 } }
+}
+
+impl<W: Write, L: ChunkLayout> WideEncoder<W, L> {
+    /// Frames chunks as described by `layout` instead.
+    pub fn with_layout<M: ChunkLayout>(self, layout: M) -> WideEncoder<W, M>
+    { WideEncoder { w: self.w, layout, pad: self.pad } }
+
+    /// Same as `Encoder::with_pad_byte`.
+    pub fn with_pad_byte(mut self, pad: u8) -> Self
+    { self.pad = pad; self }
+
+    /// Writes the header, body, checksum and padding of `chunk`. A body
+    /// whose size doesn't fit a `u32` fails with
+    /// `ErrorKind::InvalidInput` before anything is written.
+    pub fn write_chunk(&mut self, chunk: &WideChunk) -> io::Result<()> {
+        let size = u32::try_from(chunk.1.len()).map_err(|_|
+            io::Error::new(io::ErrorKind::InvalidInput, "chunk body larger than 4 GiB"))?;
+        let size = self.layout.endianness().write_u32(size);
+
+        let mut header = [0u8; 12];
+        let (id_at, size_at) = match self.layout.field_order() {
+            FieldOrder::IdThenSize => (0, 8),
+            FieldOrder::SizeThenId => (4, 0)
+        };
+
+        header[id_at..id_at + 8].copy_from_slice(&(chunk.0).0);
+        header[size_at..size_at + 4].copy_from_slice(&size);
+
+        let mut crc = Crc32::new();
+        crc.update(&(chunk.0).0);
+        crc.update(&chunk.1);
+
+        self.w.write_all(&header)?;
+        self.w.write_all(&chunk.1)?;
+        write_tail(&mut self.w, crc.finish(), chunk.1.len(), &self.layout, self.pad)
+    }
+
+    /// Unwraps the underlying writer.
+    pub fn into_inner(self) -> W { self.w }
+}